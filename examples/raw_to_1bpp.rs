@@ -0,0 +1,46 @@
+//! Converts a raw big endian Rgb565 image, like `assets/ferris.raw`, into the
+//! 1bpp format of `ImageRaw1bpp` with the most significant bit first.
+//!
+//! Pixels with a Rec. 601 luma of 128 or more are set, and set bits are drawn
+//! black, so bright shapes on a dark background come out as ink on the white
+//! panel. `assets/ferris_1bpp.raw` is made with
+//!
+//! cargo run --example raw_to_1bpp --no-default-features --features std --target x86_64-unknown-linux-gnu -- assets/ferris.raw 86 assets/ferris_1bpp.raw
+
+use std::{env, fs, process};
+
+use embassy_badger2040::ImageRaw1bpp;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let [_, input, width, output] = args.as_slice() else {
+        eprintln!("usage: raw_to_1bpp <input.raw> <width> <output.raw>");
+        process::exit(2);
+    };
+    let width: usize = width.parse().expect("width is a number");
+    let data = fs::read(input).expect("can't read input");
+    if width == 0 || data.len() % (width * 2) != 0 {
+        eprintln!("{} isn't a whole number of {} pixel rows", input, width);
+        process::exit(1);
+    }
+    let height = data.len() / (width * 2);
+
+    let row_bytes = width.div_ceil(8);
+    let mut image = vec![0; ImageRaw1bpp::data_len(width as u32, height as u32)];
+    for (i, pixel) in data.chunks_exact(2).enumerate() {
+        let (x, y) = (i % width, i / width);
+        if luma(u16::from_be_bytes([pixel[0], pixel[1]])) >= 128 {
+            image[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+        }
+    }
+    fs::write(output, image).expect("can't write output");
+    println!("{}x{} pixels", width, height);
+}
+
+/// Rec. 601 luma of an Rgb565 color, from 0 to 255.
+fn luma(color: u16) -> u32 {
+    let r = u32::from(color >> 11) * 255 / 31;
+    let g = u32::from((color >> 5) & 0x3f) * 255 / 63;
+    let b = u32::from(color & 0x1f) * 255 / 31;
+    (299 * r + 587 * g + 114 * b) / 1000
+}
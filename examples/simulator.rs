@@ -0,0 +1,49 @@
+//! Renders a screen on the host, through the emulated UC8151, into a PNG.
+//!
+//! cargo run --example simulator --no-default-features --features simulator --target x86_64-unknown-linux-gnu
+
+use core::cell::RefCell;
+
+use embassy_badger2040::{include_image, Display, Emulator, ImageRaw1bpp};
+use embedded_graphics::{
+    geometry::{Point, Size},
+    image::Image,
+    mono_font::{ascii::FONT_9X18_BOLD, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    primitives::{Primitive, PrimitiveStyle, Rectangle},
+    text::Text,
+    Drawable,
+};
+
+/// Ferris from the 86x64 Rgb565 `assets/ferris.raw`, converted by the
+/// `raw_to_1bpp` example: the bright body is black, the dark outline and
+/// eyes are white.
+const FERRIS: ImageRaw1bpp = include_image!("../assets/ferris_1bpp.raw", 86, 64);
+
+fn main() {
+    let emulator = RefCell::new(Emulator::new());
+
+    embassy_futures::block_on(async {
+        let mut display: Display<_, _, _, _> =
+            Display::new(Emulator::uc8151(&emulator)).await.unwrap();
+
+        Rectangle::new(Point::new(4, 4), Size::new(288, 120))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::Off, 2))
+            .draw(&mut display)
+            .unwrap();
+        Text::new(
+            "Hi! I'm Aron.",
+            Point::new(16, 64),
+            MonoTextStyle::new(&FONT_9X18_BOLD, BinaryColor::Off),
+        )
+        .draw(&mut display)
+        .unwrap();
+        Image::new(&FERRIS, Point::new(192, 32))
+            .draw(&mut display)
+            .unwrap();
+
+        display.push_to_display().await.unwrap();
+    });
+
+    emulator.borrow().save_png("simulator.png", 2).unwrap();
+}
@@ -0,0 +1,33 @@
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    mono_font::{ascii::FONT_9X18_BOLD, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Drawable,
+};
+use embedded_text::{
+    alignment::{HorizontalAlignment, VerticalAlignment},
+    style::{HeightMode, TextBoxStyleBuilder},
+    TextBox,
+};
+
+const BADGE_TEXT: &str = "Hi! I'm Aron.\nDon't talk to\nme about\nEmbedded Rust.";
+
+/// Draw the name badge shown by the firmware, centered text on the right of
+/// the landscape display.
+pub fn draw_badge<D: DrawTarget<Color = BinaryColor>>(target: &mut D) -> Result<(), D::Error> {
+    // Off is black on the panel, like in BMPs
+    let character_style = MonoTextStyle::new(&FONT_9X18_BOLD, BinaryColor::Off);
+    let textbox_style = TextBoxStyleBuilder::new()
+        .height_mode(HeightMode::FitToText)
+        .alignment(HorizontalAlignment::Center)
+        .vertical_alignment(VerticalAlignment::Middle)
+        .paragraph_spacing(6)
+        .build();
+    // grows to fit the text
+    let width = target.bounding_box().size.width.saturating_sub(157);
+    let bounds = Rectangle::new(Point::new(157, 10), Size::new(width, 0));
+    TextBox::with_textbox_style(BADGE_TEXT, bounds, character_style, textbox_style).draw(target)?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use embassy_rp::{
+    gpio::Output,
+    pac,
+    peripherals::SPI0,
+    spi::{self, Async, Spi},
+};
+use embassy_time::{Duration, Timer};
+use embedded_hal_async::spi::{ErrorType, Operation, SpiDevice};
+
+/// Clock pin of the panel.
+const CLK: usize = 18;
+/// Bidirectional data line of the panel, wired to MOSI.
+const DATA: usize = 19;
+
+/// GPIO function select of the SPI and SIO peripherals.
+const FUNCSEL_SPI: u8 = 1;
+const FUNCSEL_SIO: u8 = 5;
+
+/// System clock cycles per half period of the read clock, about 1 MHz at
+/// 125 MHz. The panel answers reads much slower than it accepts writes.
+const HALF_PERIOD: u32 = 64;
+
+/// SPI device of the panel on the Badger 2040, reading in 3-wire mode.
+///
+/// Writes go through the SPI peripheral with DMA. The panel answers reads on
+/// its data line, which is only connected to MOSI (GP19), so reads switch the
+/// clock and data pins over to SIO and clock the answer in by hand.
+pub struct BadgerSpi {
+    spi: Spi<'static, SPI0, Async>,
+    cs: Output<'static>,
+}
+
+impl BadgerSpi {
+    /// `spi` has to use GP18 as clock and GP19 as MOSI.
+    pub fn new(spi: Spi<'static, SPI0, Async>, cs: Output<'static>) -> Self {
+        Self { spi, cs }
+    }
+
+    async fn run(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), spi::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(buffer) => self.read(buffer),
+                Operation::Write(bytes) => self.spi.write(bytes).await?,
+                // half duplex, the panel answers after the write
+                Operation::Transfer(read, write) => {
+                    self.spi.write(write).await?;
+                    self.read(read);
+                }
+                Operation::TransferInPlace(buffer) => {
+                    self.spi.write(buffer).await?;
+                    self.read(buffer);
+                }
+                Operation::DelayNs(ns) => Timer::after(Duration::from_nanos(u64::from(*ns))).await,
+            }
+        }
+        Ok(())
+    }
+
+    /// Clock `buffer` in over the data line, in SPI mode 0.
+    fn read(&mut self, buffer: &mut [u8]) {
+        let clk = 1 << CLK;
+        let data = 1 << DATA;
+
+        // the SPI peripheral is idle after a write, take over its pins
+        pac::SIO.gpio_out(0).value_clr().write_value(clk);
+        pac::SIO.gpio_oe(0).value_set().write_value(clk);
+        pac::SIO.gpio_oe(0).value_clr().write_value(data);
+        pac::PADS_BANK0.gpio(DATA).modify(|w| w.set_ie(true));
+        for pin in [CLK, DATA] {
+            pac::IO_BANK0
+                .gpio(pin)
+                .ctrl()
+                .write(|w| w.set_funcsel(FUNCSEL_SIO));
+        }
+
+        for byte in buffer.iter_mut() {
+            for _ in 0..8 {
+                // the panel shifts out on the falling edge
+                pac::SIO.gpio_out(0).value_set().write_value(clk);
+                cortex_m::asm::delay(HALF_PERIOD);
+                let bit = pac::SIO.gpio_in(0).read() & data != 0;
+                *byte = *byte << 1 | bit as u8;
+                pac::SIO.gpio_out(0).value_clr().write_value(clk);
+                cortex_m::asm::delay(HALF_PERIOD);
+            }
+        }
+
+        for pin in [CLK, DATA] {
+            pac::IO_BANK0
+                .gpio(pin)
+                .ctrl()
+                .write(|w| w.set_funcsel(FUNCSEL_SPI));
+        }
+    }
+}
+
+impl ErrorType for BadgerSpi {
+    type Error = spi::Error;
+}
+
+impl SpiDevice for BadgerSpi {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), spi::Error> {
+        self.cs.set_low();
+        let result = self.run(operations).await;
+        self.cs.set_high();
+        result
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{Framebuffer, InvalidImage, OutOfBounds, Resolution};
+use embedded_graphics::{
+    geometry::{OriginDimensions, Point},
+    image::Image,
+    pixelcolor::BinaryColor,
+    Drawable,
+};
+use tinybmp::Bmp;
+
+/// File header, info header and the two entry color table.
+const HEADER_LEN: usize = 14 + 40 + 2 * 4;
+
+/// 2835 pixels per meter, 72 dpi.
+const RESOLUTION: u32 = 2835;
+
+impl<R: Resolution> Framebuffer<R> {
+    /// Bytes per BMP row, padded to 4 bytes.
+    const BMP_ROW: usize = Self::WIDTH.div_ceil(32) * 4;
+
+    /// Length of the BMP written by [`Self::to_bmp`].
+    pub const BMP_LEN: usize = HEADER_LEN + Self::BMP_ROW * Self::HEIGHT;
+
+    /// Encode as 1bpp BMP in landscape like [`Rotation::Deg0`] into `out`,
+    /// returning the number of bytes written.
+    ///
+    /// `out` needs at least [`Self::BMP_LEN`] bytes.
+    ///
+    /// [`Rotation::Deg0`]: crate::Rotation::Deg0
+    pub fn to_bmp(&self, out: &mut [u8]) -> Result<usize, OutOfBounds> {
+        let out = out.get_mut(..Self::BMP_LEN).ok_or(OutOfBounds)?;
+        out.fill(0);
+
+        let (header, data) = out.split_at_mut(HEADER_LEN);
+        let mut len = 0;
+        let mut put = |bytes: &[u8]| {
+            header[len..len + bytes.len()].copy_from_slice(bytes);
+            len += bytes.len();
+        };
+        // file header
+        put(b"BM");
+        put(&(Self::BMP_LEN as u32).to_le_bytes());
+        put(&[0; 4]);
+        put(&(HEADER_LEN as u32).to_le_bytes());
+        // info header, positive height for rows stored bottom up
+        put(&40u32.to_le_bytes());
+        put(&(Self::WIDTH as i32).to_le_bytes());
+        put(&(Self::HEIGHT as i32).to_le_bytes());
+        put(&1u16.to_le_bytes());
+        put(&1u16.to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&(data.len() as u32).to_le_bytes());
+        put(&RESOLUTION.to_le_bytes());
+        put(&RESOLUTION.to_le_bytes());
+        put(&2u32.to_le_bytes());
+        put(&2u32.to_le_bytes());
+        // color table, index 0 black and index 1 white
+        put(&[0x00, 0x00, 0x00, 0x00]);
+        put(&[0xff, 0xff, 0xff, 0x00]);
+
+        for (row, line) in data.chunks_exact_mut(Self::BMP_ROW).enumerate() {
+            let y = Self::HEIGHT - 1 - row;
+            for x in 0..Self::WIDTH {
+                if !self.get(x, y).unwrap_or_default() {
+                    line[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        Ok(Self::BMP_LEN)
+    }
+
+    /// Decode a BMP of exactly `WIDTH` x `HEIGHT` pixels.
+    ///
+    /// Any format [`tinybmp`] reads is accepted, colors other than 1bpp are
+    /// converted to [`BinaryColor`] by brightness.
+    pub fn from_bmp(data: &[u8]) -> Result<Self, InvalidImage> {
+        let bmp = Bmp::<BinaryColor>::from_slice(data).map_err(|_| InvalidImage)?;
+        let mut framebuffer = Self::default();
+        if bmp.size() != framebuffer.size() {
+            return Err(InvalidImage);
+        }
+
+        let _ = Image::new(&bmp, Point::zero()).draw(&mut framebuffer);
+        Ok(framebuffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_format::{self, Fb, ImageFormat};
+
+    struct Bmp;
+
+    impl ImageFormat for Bmp {
+        fn len<R: Resolution>() -> usize {
+            Framebuffer::<R>::BMP_LEN
+        }
+
+        fn encode<R: Resolution>(
+            framebuffer: &Framebuffer<R>,
+            out: &mut [u8],
+        ) -> Result<usize, OutOfBounds> {
+            framebuffer.to_bmp(out)
+        }
+
+        fn decode<R: Resolution>(data: &[u8]) -> Result<Framebuffer<R>, InvalidImage> {
+            Framebuffer::from_bmp(data)
+        }
+    }
+
+    #[test]
+    fn bmp_round_trip() {
+        let buffer = image_format::round_trip::<Bmp>();
+        assert!(buffer.starts_with(b"BM"));
+        // the last row comes first, black pixels are cleared bits
+        assert_eq!(buffer[HEADER_LEN], 0b1101_1110);
+    }
+
+    #[test]
+    fn to_bmp_needs_room() {
+        image_format::needs_room::<Bmp>();
+    }
+
+    #[test]
+    fn from_bmp_rejects_other_sizes() {
+        image_format::rejects_other_sizes::<Bmp>();
+    }
+
+    #[test]
+    fn from_bmp_rejects_truncated_raster() {
+        image_format::rejects_truncated::<Bmp>();
+        let mut buffer = [0; Fb::BMP_LEN];
+        Fb::stripes().to_bmp(&mut buffer).unwrap();
+        assert!(matches!(
+            Fb::from_bmp(&buffer[..HEADER_LEN - 4]),
+            Err(InvalidImage)
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{BoosterFlags, PllFlags, PwrFlags1, PwrFlags2};
+
+/// Power and timing settings applied by [`Uc8151::init`](crate::Uc8151::init).
+///
+/// The defaults match the values used by the Pimoroni driver. Raising the
+/// source voltages or the VCOM level can bring back contrast on aged panels,
+/// a lower frame rate saves power at the cost of slower refreshes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Uc8151Config {
+    pub(crate) power_source: PwrFlags1,
+    pub(crate) gate_voltage: PwrFlags2,
+    pub(crate) source_high: u8,
+    pub(crate) source_low: u8,
+    pub(crate) source_red: u8,
+    pub(crate) booster: [BoosterFlags; 3],
+    pub(crate) frame_rate: Option<PllFlags>,
+    pub(crate) vcom: Option<u8>,
+}
+
+impl Default for Uc8151Config {
+    fn default() -> Self {
+        let booster =
+            BoosterFlags::START_10MS | BoosterFlags::STRENGTH_3 | BoosterFlags::OFF_6_58US;
+        Self {
+            power_source: PwrFlags1::VDS_INTERNAL | PwrFlags1::VDG_INTERNAL,
+            gate_voltage: PwrFlags2::VCOM_VD | PwrFlags2::VGHL_16V,
+            source_high: 0b101011,
+            source_low: 0b101011,
+            source_red: 0b101011,
+            booster: [booster; 3],
+            frame_rate: None,
+            vcom: None,
+        }
+    }
+}
+
+impl Uc8151Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate VDH/VDL (source) and VGH/VGL (gate) internally or take them
+    /// from external supplies.
+    pub fn with_power_source(mut self, power_source: PwrFlags1) -> Self {
+        self.power_source = power_source;
+        self
+    }
+
+    /// VGH/VGL gate voltage level and the VCOM voltage source.
+    pub fn with_gate_voltage(mut self, gate_voltage: PwrFlags2) -> Self {
+        self.gate_voltage = gate_voltage;
+        self
+    }
+
+    /// VDH and VDL source voltage levels, `2.4 V + 0.2 V * level` (negative
+    /// for VDL) up to 11 V at level 43.
+    pub fn with_source_voltage(mut self, high: u8, low: u8) -> Self {
+        self.source_high = high.min(0b101011);
+        self.source_low = low.min(0b101011);
+        self
+    }
+
+    /// VDHR source voltage level for red pixels, same scale as
+    /// [`Self::with_source_voltage`].
+    pub fn with_red_voltage(mut self, level: u8) -> Self {
+        self.source_red = level.min(0b101011);
+        self
+    }
+
+    /// Booster soft start settings for phase A, B and C.
+    pub fn with_booster(mut self, a: BoosterFlags, b: BoosterFlags, c: BoosterFlags) -> Self {
+        self.booster = [a, b, c];
+        self
+    }
+
+    /// Fixed frame rate for every color format and gray levels, instead of
+    /// the one picked for the [`RefreshSpeed`](crate::RefreshSpeed).
+    pub fn with_frame_rate(mut self, frame_rate: PllFlags) -> Self {
+        self.frame_rate = Some(frame_rate);
+        self
+    }
+
+    /// VCOM DC level, `-0.1 V - 0.05 V * level` down to -3 V at level 58.
+    ///
+    /// The controller default (level 0) is used unless this is set.
+    pub fn with_vcom(mut self, level: u8) -> Self {
+        self.vcom = Some(level.min(0b111010));
+        self
+    }
+}
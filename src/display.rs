@@ -1,96 +1,627 @@
-use crate::Uc8151;
+#[cfg(feature = "rp2040")]
+use crate::BadgerSpi;
+use crate::{
+    composite, Border, ColorFormat, Error, Framebuffer, Layer, OutOfBounds, RefreshPolicy,
+    RefreshSpeed, Res128x296, Resolution, Uc8151, Uc8151Config,
+};
+#[cfg(feature = "rp2040")]
+use embassy_rp::gpio::{Input, Output};
+use embassy_time::Instant;
 use embedded_graphics::{
     draw_target::DrawTarget,
-    geometry::{Dimensions, OriginDimensions, Size},
+    geometry::{Dimensions, OriginDimensions, Point, Size},
     pixelcolor::BinaryColor,
+    primitives::{PointsIter, Rectangle},
     Pixel,
 };
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, spi::SpiDevice};
 
-impl Default for Framebuffer {
-    fn default() -> Self {
-        Self {
-            bits: [0; (Self::HEIGHT * Self::WIDTH) as usize / 8],
-        }
-    }
+/// Rotation of the display contents, clockwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// Landscape, 296x128.
+    #[default]
+    Deg0,
+    /// Portrait, 128x296.
+    Deg90,
+    /// Landscape, upside down.
+    Deg180,
+    /// Portrait, upside down.
+    Deg270,
 }
 
-pub struct Framebuffer {
-    bits: [u8; (Self::HEIGHT * Self::WIDTH) as usize / 8],
+/// Mirroring of the display contents, applied before rotation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mirror {
+    #[default]
+    None,
+    Horizontal,
+    Vertical,
+    Both,
 }
 
-impl Framebuffer {
-    pub const HEIGHT: usize = 128;
-    pub const WIDTH: usize = 297;
+/// Most gate lines the UC8151 drives, the widest framebuffer.
+const MAX_WIDTH: usize = 296;
 
-    pub fn write(&mut self, x: usize, y: usize, value: bool) {
-        if x >= Framebuffer::WIDTH || y >= Framebuffer::HEIGHT {
-            // TODO: error out
-            log::error!("Ignoring out of bounds pixel draw at x: {x} y:{y}");
-            return;
-        }
+/// Pixels collected for one byte row of the framebuffer, written out with a
+/// single masked store per column.
+struct Band {
+    /// Bytes per framebuffer column.
+    stride: usize,
+    /// Byte row within the columns, `y / 8`.
+    row: usize,
+    /// Columns touched since the last flush.
+    first: usize,
+    last: usize,
+    values: [u8; MAX_WIDTH],
+    masks: [u8; MAX_WIDTH],
+}
 
-        let address = ((y / 8) + (x * (Self::HEIGHT / 8))) as usize;
+impl Band {
+    fn new(stride: usize) -> Self {
+        Self {
+            stride,
+            row: 0,
+            first: MAX_WIDTH,
+            last: 0,
+            values: [0; MAX_WIDTH],
+            masks: [0; MAX_WIDTH],
+        }
+    }
 
-        let o: u8 = 7 - (y as u8 & 0b111); // bit offset within byte
-        let m: u8 = !(1 << o); // bit mask for byte
-        let b: u8 = ((value) as u8) << o; // bit value shifted to position
-        self.bits[address] = (self.bits[address] & m) | b;
+    /// Collect a pixel, flushing first when it lies in another byte row.
+    fn set(&mut self, bytes: &mut [u8], x: usize, y: usize, black: bool) {
+        if y / 8 != self.row {
+            self.flush(bytes);
+            self.row = y / 8;
+        }
+        let bit = 0x80 >> (y % 8);
+        self.masks[x] |= bit;
+        if black {
+            self.values[x] |= bit;
+        }
+        self.first = self.first.min(x);
+        self.last = self.last.max(x);
     }
 
-    pub fn real(&self, _x: usize, _y: usize) -> Option<bool> {
-        None
+    /// Write the collected pixels into `bytes`, a framebuffer in panel layout.
+    fn flush(&mut self, bytes: &mut [u8]) {
+        for x in self.first..=self.last {
+            let byte = &mut bytes[x * self.stride + self.row];
+            *byte = (*byte & !self.masks[x]) | self.values[x];
+            self.masks[x] = 0;
+            self.values[x] = 0;
+        }
+        self.first = MAX_WIDTH;
+        self.last = 0;
     }
 }
 
-pub struct Display {
-    uc8151: Uc8151,
-    framebuffer: Framebuffer,
+/// Black and white display, drawn on with embedded-graphics.
+///
+/// With `L` layers, draws go to the selected [`Layer`] and the framebuffer
+/// holds the composited layers, rebuilt on every push.
+pub struct Display<SPI, DC, RESET, BUSY, R: Resolution = Res128x296, const L: usize = 0> {
+    uc8151: Uc8151<SPI, DC, RESET, BUSY, R>,
+    framebuffer: Framebuffer<R>,
+    layers: [Layer<R>; L],
+    /// Layer receiving draws.
+    active: usize,
+    /// Region of the framebuffer changed since the last refresh.
+    dirty: Option<Rectangle>,
+    rotation: Rotation,
+    mirror: Mirror,
+    policy: RefreshPolicy,
 }
 
-impl Display {
+/// Display of the Badger 2040.
+#[cfg(feature = "rp2040")]
+pub type BadgerDisplay<const L: usize = 0> =
+    Display<BadgerSpi, Output<'static>, Output<'static>, Input<'static>, Res128x296, L>;
+
+impl<SPI, DC, RESET, BUSY, R, const L: usize> Display<SPI, DC, RESET, BUSY, R, L>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
     /// Initialize display from Uc8151.
-    pub async fn new(mut uc8151: Uc8151) -> Self {
-        uc8151.init().await;
-        Self {
+    ///
+    /// The controller is switched to [`ColorFormat::BlackWhite`] and
+    /// initialized with the settings of its last [`Uc8151::init`], or the
+    /// defaults.
+    pub async fn new(
+        mut uc8151: Uc8151<SPI, DC, RESET, BUSY, R>,
+    ) -> Result<Self, Error<SPI::Error>> {
+        uc8151.set_format(ColorFormat::BlackWhite);
+        uc8151.init(uc8151.config()).await?;
+        Ok(Self {
             framebuffer: Framebuffer::default(),
+            layers: core::array::from_fn(|_| Layer::default()),
+            active: 0,
             uc8151,
+            dirty: Some(Self::full_area()),
+            rotation: Rotation::default(),
+            mirror: Mirror::default(),
+            policy: RefreshPolicy::default(),
+        })
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Rotate the display, this changes [`Self::size`] for all following draws.
+    ///
+    /// Already drawn contents are left as they are.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    pub fn mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    /// Mirror the display, applies to all following draws.
+    pub fn set_mirror(&mut self, mirror: Mirror) {
+        self.mirror = mirror;
+    }
+
+    pub fn layer(&self, index: usize) -> Option<&Layer<R>> {
+        self.layers.get(index)
+    }
+
+    /// Mutable access to a layer, to change its contents, blend mode or
+    /// visibility. Marks the whole display as changed.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer<R>> {
+        self.mark_dirty(Self::full_area());
+        self.layers.get_mut(index)
+    }
+
+    /// Index of the layer receiving draws.
+    pub fn active_layer(&self) -> usize {
+        self.active
+    }
+
+    /// Send all following draws to the layer at `index`.
+    pub fn select_layer(&mut self, index: usize) -> Result<(), OutOfBounds> {
+        if index >= L {
+            return Err(OutOfBounds);
+        }
+        self.active = index;
+        Ok(())
+    }
+
+    /// Framebuffer receiving draws, the active layer if there are any.
+    fn target(&mut self) -> &mut Framebuffer<R> {
+        match self.layers.get_mut(self.active) {
+            Some(layer) => layer.framebuffer_mut(),
+            None => &mut self.framebuffer,
+        }
+    }
+
+    /// Map a point in display coordinates to framebuffer coordinates.
+    fn to_framebuffer(&self, point: Point) -> Point {
+        let size = self.size();
+        let (width, height) = (size.width as i32, size.height as i32);
+        let Point { mut x, mut y } = point;
+
+        if matches!(self.mirror, Mirror::Horizontal | Mirror::Both) {
+            x = width - 1 - x;
+        }
+        if matches!(self.mirror, Mirror::Vertical | Mirror::Both) {
+            y = height - 1 - y;
+        }
+
+        match self.rotation {
+            Rotation::Deg0 => Point::new(x, y),
+            Rotation::Deg90 => Point::new(y, width - 1 - x),
+            Rotation::Deg180 => Point::new(width - 1 - x, height - 1 - y),
+            Rotation::Deg270 => Point::new(height - 1 - y, x),
+        }
+    }
+
+    fn full_area() -> Rectangle {
+        Rectangle::new(Point::zero(), Size::new(R::WIDTH as u32, R::HEIGHT as u32))
+    }
+
+    /// Extend the dirty region to cover `area`.
+    fn mark_dirty(&mut self, area: Rectangle) {
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => {
+                let dirty_bottom_right = dirty.bottom_right().unwrap();
+                Rectangle::with_corners(
+                    dirty.top_left.component_min(area.top_left),
+                    dirty_bottom_right.component_max(bottom_right),
+                )
+            }
+            None => area,
+        });
+    }
+
+    /// Write changed part of framebuffer to display and refresh it.
+    ///
+    /// Only the byte-aligned window around the pixels drawn since the last
+    /// refresh is sent, falling back to a full refresh when everything changed.
+    /// On failure the region stays dirty, so the push can be retried.
+    pub async fn push_to_display(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.start_push_to_display().await?;
+        self.wait_for_refresh().await
+    }
+
+    /// Like [`Self::push_to_display`], but returns as soon as the refresh has
+    /// started instead of waiting seconds for it to finish.
+    ///
+    /// Drawing can continue right away, the changes go out with the next
+    /// push. Call [`Self::wait_for_refresh`] before that, starting another
+    /// refresh while one is running fails with [`Error::RefreshInProgress`].
+    /// Clean refreshes forced by the [`RefreshPolicy`] are always waited for.
+    pub async fn start_push_to_display(&mut self) -> Result<(), Error<SPI::Error>> {
+        let Some(dirty) = self.dirty.take() else {
+            return Ok(());
+        };
+        if L > 0 {
+            composite(&self.layers, &mut self.framebuffer);
+        }
+
+        // align rows to whole bytes
+        let top = dirty.top_left.y & !0b111;
+        let bottom = (dirty.top_left.y + dirty.size.height as i32 + 0b111) & !0b111;
+        let window = Rectangle::with_corners(
+            Point::new(dirty.top_left.x, top),
+            Point::new(dirty.top_left.x + dirty.size.width as i32 - 1, bottom - 1),
+        );
+
+        let now = Instant::now();
+        let result = if self.policy.needs_clean(now) {
+            self.clean_refresh().await
+        } else if window == Self::full_area() {
+            self.uc8151.start_update(self.framebuffer.as_bytes()).await
+        } else {
+            self.uc8151
+                .start_partial_update(self.framebuffer.as_bytes(), window)
+                .await
+        };
+        match result {
+            Ok(()) => self.record_refresh(window == Self::full_area(), now),
+            Err(_) => self.mark_dirty(dirty),
+        }
+        result
+    }
+
+    /// Whether a refresh was started and not yet waited for.
+    pub fn is_refreshing(&self) -> bool {
+        self.uc8151.is_refreshing()
+    }
+
+    /// Wait until the refresh started by [`Self::start_push_to_display`]
+    /// finishes, returns immediately if none is running.
+    pub async fn wait_for_refresh(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.finish_refresh().await
+    }
+
+    /// Put the panel into deep sleep until the next push.
+    ///
+    /// The framebuffer and the panel contents are kept.
+    pub async fn sleep(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.sleep().await
+    }
+
+    /// Wake the panel from deep sleep ahead of the next push.
+    pub async fn wake(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.wake().await
+    }
+
+    /// Change how fast (and how cleanly) the panel refreshes.
+    pub async fn set_refresh_speed(
+        &mut self,
+        speed: RefreshSpeed,
+    ) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.set_speed(speed).await
+    }
+
+    pub fn uc8151(&self) -> &Uc8151<SPI, DC, RESET, BUSY, R> {
+        &self.uc8151
+    }
+
+    /// Direct access to the controller, for example to read its status.
+    pub fn uc8151_mut(&mut self) -> &mut Uc8151<SPI, DC, RESET, BUSY, R> {
+        &mut self.uc8151
+    }
+
+    /// Reinitialize the controller with new power and timing settings.
+    ///
+    /// Marks the whole display as changed so the next push redraws it.
+    pub async fn reconfigure(&mut self, config: Uc8151Config) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.init(config).await?;
+        self.mark_dirty(Self::full_area());
+        Ok(())
+    }
+
+    /// Change the border color, marks the whole display as changed so the next
+    /// push shows it.
+    pub async fn set_border(&mut self, border: Border) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.set_border(border).await?;
+        self.mark_dirty(Self::full_area());
+        Ok(())
+    }
+
+    /// Swap black and white on the panel, the framebuffer stays as it is.
+    ///
+    /// Marks the whole display as changed so the next push shows it.
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), Error<SPI::Error>> {
+        self.uc8151.set_inverted(inverted).await?;
+        self.mark_dirty(Self::full_area());
+        Ok(())
+    }
+
+    /// Write entire framebuffer to display and refresh.
+    pub async fn push_full_to_display(&mut self) -> Result<(), Error<SPI::Error>> {
+        if L > 0 {
+            composite(&self.layers, &mut self.framebuffer);
+        }
+        let now = Instant::now();
+        if self.policy.needs_clean(now) {
+            self.clean_refresh().await?;
+        } else {
+            self.uc8151.update(self.framebuffer.as_bytes()).await?;
+        }
+        self.record_refresh(true, now);
+        self.dirty = None;
+        Ok(())
+    }
+
+    /// Refresh with the slow waveform, flashing first if the policy asks to.
+    async fn clean_refresh(&mut self) -> Result<(), Error<SPI::Error>> {
+        if self.policy.flash() {
+            self.uc8151.flash().await?;
+        }
+        self.uc8151.clean_update(self.framebuffer.as_bytes()).await
+    }
+
+    /// Count a successful refresh towards the policy.
+    fn record_refresh(&mut self, full: bool, now: Instant) {
+        if self.policy.needs_clean(now) || (full && self.uc8151.speed() == RefreshSpeed::Normal) {
+            self.policy.record_clean(now);
+        } else {
+            self.policy.record_fast();
         }
     }
 
-    /// Write current framebuffer to display and refresh
-    pub async fn push_to_display(&mut self) {
-        self.uc8151.update(&self.framebuffer.bits).await;
+    pub fn refresh_policy(&self) -> &RefreshPolicy {
+        &self.policy
+    }
+
+    /// Set when refreshes are forced to be clean, to keep ghosting in check.
+    pub fn set_refresh_policy(&mut self, policy: RefreshPolicy) {
+        self.policy = policy;
+    }
+
+    /// Write entire framebuffer to display, driving every pixel through both
+    /// colors `passes` times to remove ghosting. Slow, see [`Uc8151::deep_clean`].
+    pub async fn deep_clean(&mut self, passes: usize) -> Result<(), Error<SPI::Error>> {
+        if L > 0 {
+            composite(&self.layers, &mut self.framebuffer);
+        }
+        self.uc8151
+            .deep_clean(self.framebuffer.as_bytes(), passes)
+            .await?;
+        self.policy.record_clean(Instant::now());
+        self.dirty = None;
+        Ok(())
     }
 
-    /// Clear framebuffer - call [Self::push_to_display] to clear display.
+    /// Clear framebuffer (or the active layer) - call [Self::push_to_display] to clear display.
     pub async fn clear_buffer(&mut self) {
-        self.framebuffer.bits.fill(0);
+        self.target().fill(false);
+        self.mark_dirty(Self::full_area());
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer<R> {
+        &self.framebuffer
+    }
+
+    /// Mutable access to the framebuffer, marks all of it as changed.
+    ///
+    /// With layers, changes are overwritten by the next push.
+    pub fn framebuffer_mut(&mut self) -> &mut Framebuffer<R> {
+        self.mark_dirty(Self::full_area());
+        &mut self.framebuffer
     }
 }
 
-impl DrawTarget for Display {
+impl<SPI, DC, RESET, BUSY, R, const L: usize> DrawTarget for Display<SPI, DC, RESET, BUSY, R, L>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
     type Color = BinaryColor;
-    type Error = core::convert::Infallible;
+    type Error = Error<SPI::Error>;
     // adapted from https://github.com/9names/uc8151-rs
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
         let bb = self.bounding_box();
+        let mut changed: Option<(Point, Point)> = None;
 
-        pixels
-            .into_iter()
-            .filter(|Pixel(pos, _color)| bb.contains(*pos))
-            .for_each(|Pixel(pos, color)| {
-                self.framebuffer
-                    .write(pos.x as _, pos.y as _, color == BinaryColor::Off);
+        for Pixel(pos, color) in pixels.into_iter().filter(|Pixel(pos, _)| bb.contains(*pos)) {
+            let pos = self.to_framebuffer(pos);
+            self.target()
+                .write(pos.x as _, pos.y as _, color == BinaryColor::Off)?;
+            changed = Some(match changed {
+                Some((min, max)) => (min.component_min(pos), max.component_max(pos)),
+                None => (pos, pos),
             });
+        }
+
+        if let Some((min, max)) = changed {
+            self.mark_dirty(Rectangle::with_corners(min, max));
+        }
+
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = clipped.bottom_right() else {
+            return Ok(());
+        };
+        if R::WIDTH > MAX_WIDTH {
+            let pixels = area.points().zip(colors);
+            return self.draw_iter(pixels.map(|(pos, color)| Pixel(pos, color)));
+        }
+
+        // rotating and mirroring are affine, so the framebuffer position moves
+        // by a fixed step along display columns and rows
+        let origin = self.to_framebuffer(area.top_left);
+        let step_x = self.to_framebuffer(area.top_left + Point::new(1, 0)) - origin;
+        let step_y = self.to_framebuffer(area.top_left + Point::new(0, 1)) - origin;
+        let dirty = Rectangle::with_corners(
+            self.to_framebuffer(clipped.top_left),
+            self.to_framebuffer(bottom_right),
+        );
+
+        let bytes = self.target().as_bytes_mut();
+        let mut band = Band::new(R::HEIGHT / 8);
+        let mut colors = colors.into_iter();
+        'rows: for row in 0..area.size.height as i32 {
+            for column in 0..area.size.width as i32 {
+                let Some(color) = colors.next() else {
+                    break 'rows;
+                };
+                if !clipped.contains(area.top_left + Point::new(column, row)) {
+                    continue;
+                }
+                let pos = origin + step_x * column + step_y * row;
+                band.set(
+                    bytes,
+                    pos.x as usize,
+                    pos.y as usize,
+                    color == BinaryColor::Off,
+                );
+            }
+        }
+        band.flush(bytes);
+
+        self.mark_dirty(dirty);
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = clipped.bottom_right() else {
+            return Ok(());
+        };
+
+        // rotating and mirroring keeps rectangles axis aligned, so only the
+        // corners need mapping
+        let area = Rectangle::with_corners(
+            self.to_framebuffer(clipped.top_left),
+            self.to_framebuffer(bottom_right),
+        );
+        self.target().fill_rect(area, color == BinaryColor::Off);
+        self.mark_dirty(area);
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target().fill(color == BinaryColor::Off);
+        self.mark_dirty(Self::full_area());
 
         Ok(())
     }
 }
-impl OriginDimensions for Display {
+impl<SPI, DC, RESET, BUSY, R: Resolution, const L: usize> OriginDimensions
+    for Display<SPI, DC, RESET, BUSY, R, L>
+{
     fn size(&self) -> Size {
-        Size::new(Framebuffer::WIDTH as u32, Framebuffer::HEIGHT as u32)
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => Size::new(R::WIDTH as u32, R::HEIGHT as u32),
+            Rotation::Deg90 | Rotation::Deg270 => Size::new(R::HEIGHT as u32, R::WIDTH as u32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Emulator;
+    use core::cell::RefCell;
+    use embassy_futures::block_on;
+    use std::vec::Vec;
+
+    #[test]
+    fn fill_contiguous_matches_draw_iter() {
+        let areas = [
+            Rectangle::new(Point::new(3, 5), Size::new(29, 21)),
+            Rectangle::new(Point::new(-7, -2), Size::new(40, 17)),
+            Rectangle::new(Point::new(280, 100), Size::new(30, 40)),
+            Rectangle::new(Point::new(9, 9), Size::new(1, 1)),
+        ];
+        let rotations = [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ];
+        let mirrors = [
+            Mirror::None,
+            Mirror::Horizontal,
+            Mirror::Vertical,
+            Mirror::Both,
+        ];
+
+        let packed = RefCell::new(Emulator::new());
+        let reference = RefCell::new(Emulator::new());
+        block_on(async {
+            let mut packed: Display<_, _, _, _> =
+                Display::new(Emulator::uc8151(&packed)).await.unwrap();
+            let mut reference: Display<_, _, _, _> =
+                Display::new(Emulator::uc8151(&reference)).await.unwrap();
+
+            for rotation in rotations {
+                for mirror in mirrors {
+                    for area in areas {
+                        for display in [&mut packed, &mut reference] {
+                            display.set_rotation(rotation);
+                            display.set_mirror(mirror);
+                            display.clear(BinaryColor::On).unwrap();
+                        }
+                        // stripes which differ per row and column
+                        let colors: Vec<_> = area
+                            .points()
+                            .map(|p| BinaryColor::from((p.x * 3 + p.y * 5) % 7 < 3))
+                            .collect();
+                        packed
+                            .fill_contiguous(&area, colors.iter().copied())
+                            .unwrap();
+                        reference
+                            .draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)))
+                            .unwrap();
+                        assert!(
+                            packed.framebuffer().as_bytes() == reference.framebuffer().as_bytes(),
+                            "{:?} {:?} {:?}",
+                            rotation,
+                            mirror,
+                            area
+                        );
+                    }
+                }
+            }
+        });
     }
 }
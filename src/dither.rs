@@ -0,0 +1,250 @@
+use core::marker::PhantomData;
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    pixelcolor::{BinaryColor, Gray8, GrayColor, PixelColor},
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// Algorithm used to turn shades into black and white pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DitherAlgorithm {
+    /// Error diffusion to four neighbours, smooth gradients.
+    #[default]
+    FloydSteinberg,
+    /// Error diffusion to six neighbours, dropping a quarter of the error
+    /// for more contrast.
+    Atkinson,
+    /// Ordered dithering with a 4x4 Bayer matrix, no state.
+    Bayer,
+}
+
+/// 4x4 Bayer threshold matrix.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Dithering state for images up to `W` pixels wide.
+///
+/// Pixels are expected row by row, left to right. The error carried to the
+/// following rows is kept per row and dropped when a row is skipped.
+pub struct Ditherer<const W: usize = 296> {
+    algorithm: DitherAlgorithm,
+    /// Accumulated error for the current and the next two rows.
+    errors: [[i16; W]; 3],
+    row: Option<i32>,
+}
+
+impl<const W: usize> Ditherer<W> {
+    pub fn new(algorithm: DitherAlgorithm) -> Self {
+        Self {
+            algorithm,
+            errors: [[0; W]; 3],
+            row: None,
+        }
+    }
+
+    pub fn algorithm(&self) -> DitherAlgorithm {
+        self.algorithm
+    }
+
+    /// Forget the error carried over from previous pixels.
+    pub fn reset(&mut self) {
+        self.errors = [[0; W]; 3];
+        self.row = None;
+    }
+
+    /// Move the error rows along so that the first one belongs to row `y`.
+    fn advance_to(&mut self, y: i32) {
+        match self.row {
+            Some(row) if row == y => {}
+            Some(row) if row + 1 == y => {
+                self.errors.rotate_left(1);
+                self.errors[2] = [0; W];
+            }
+            Some(row) if row + 2 == y => {
+                self.errors.rotate_left(2);
+                self.errors[1] = [0; W];
+                self.errors[2] = [0; W];
+            }
+            _ => self.errors = [[0; W]; 3],
+        }
+        self.row = Some(y);
+    }
+
+    /// Add `error` times `weight / divisor` to the pixel at `dx`, `dy` from `x`.
+    fn spread(&mut self, x: usize, dx: isize, dy: usize, error: i16, weight: i16, divisor: i16) {
+        let Some(x) = x.checked_add_signed(dx) else {
+            return;
+        };
+        if x < W {
+            self.errors[dy][x] += error * weight / divisor;
+        }
+    }
+
+    /// Dither a single pixel of brightness `luma` at `point`.
+    pub fn dither(&mut self, point: Point, luma: u8) -> BinaryColor {
+        if self.algorithm == DitherAlgorithm::Bayer {
+            let threshold = BAYER[(point.y & 3) as usize][(point.x & 3) as usize] * 16 + 8;
+            return (luma > threshold).into();
+        }
+
+        if point.x < 0 || point.x as usize >= W {
+            return (luma >= 128).into();
+        }
+        let x = point.x as usize;
+
+        self.advance_to(point.y);
+        let value = luma as i16 + self.errors[0][x];
+        let on = value >= 128;
+        let error = value - if on { 255 } else { 0 };
+
+        match self.algorithm {
+            DitherAlgorithm::FloydSteinberg => {
+                self.spread(x, 1, 0, error, 7, 16);
+                self.spread(x, -1, 1, error, 3, 16);
+                self.spread(x, 0, 1, error, 5, 16);
+                self.spread(x, 1, 1, error, 1, 16);
+            }
+            DitherAlgorithm::Atkinson => {
+                self.spread(x, 1, 0, error, 1, 8);
+                self.spread(x, 2, 0, error, 1, 8);
+                self.spread(x, -1, 1, error, 1, 8);
+                self.spread(x, 0, 1, error, 1, 8);
+                self.spread(x, 1, 1, error, 1, 8);
+                self.spread(x, 0, 2, error, 1, 8);
+            }
+            DitherAlgorithm::Bayer => unreachable!(),
+        }
+
+        on.into()
+    }
+}
+
+/// Draw target accepting grayscale or color pixels and dithering them onto a
+/// black and white target such as [`Display`](crate::Display).
+///
+/// Works with any color convertible to [`Gray8`], such as `Gray8`, `Rgb565`
+/// or `Rgb888`. The error diffusion state is reset for every draw call.
+pub struct DitherTarget<'a, D, C, const W: usize = 296> {
+    target: &'a mut D,
+    ditherer: Ditherer<W>,
+    color: PhantomData<C>,
+}
+
+impl<'a, D, C, const W: usize> DitherTarget<'a, D, C, W>
+where
+    D: DrawTarget<Color = BinaryColor>,
+    C: PixelColor + Into<Gray8>,
+{
+    pub fn new(target: &'a mut D, algorithm: DitherAlgorithm) -> Self {
+        Self {
+            target,
+            ditherer: Ditherer::new(algorithm),
+            color: PhantomData,
+        }
+    }
+}
+
+impl<'a, D, C, const W: usize> Dimensions for DitherTarget<'a, D, C, W>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<'a, D, C, const W: usize> DrawTarget for DitherTarget<'a, D, C, W>
+where
+    D: DrawTarget<Color = BinaryColor>,
+    C: PixelColor + Into<Gray8>,
+{
+    type Color = C;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let Self {
+            target, ditherer, ..
+        } = self;
+        ditherer.reset();
+
+        target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(pos, color)| Pixel(pos, ditherer.dither(pos, color.into().luma()))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ops::Range;
+    use std::{string::String, vec::Vec};
+
+    /// Dither `rows` of an 8 pixel wide image, `#` for black and `.` for white.
+    fn render(
+        ditherer: &mut Ditherer<8>,
+        rows: Range<i32>,
+        luma: impl Fn(i32, i32) -> u8,
+    ) -> Vec<String> {
+        rows.map(|y| {
+            (0..8)
+                .map(|x| match ditherer.dither(Point::new(x, y), luma(x, y)) {
+                    BinaryColor::Off => '#',
+                    BinaryColor::On => '.',
+                })
+                .collect()
+        })
+        .collect()
+    }
+
+    fn gradient(x: i32, _y: i32) -> u8 {
+        (x * 32 + 16) as u8
+    }
+
+    #[test]
+    fn bayer_thresholds_flat_gray() {
+        let mut ditherer = Ditherer::new(DitherAlgorithm::Bayer);
+        assert_eq!(
+            render(&mut ditherer, 0..4, |_, _| 128),
+            [".#.#.#.#", "#.#.#.#.", ".#.#.#.#", "#.#.#.#."]
+        );
+        assert_eq!(
+            render(&mut ditherer, 0..4, |_, _| 64),
+            [".#.#.#.#", "########", ".#.#.#.#", "########"]
+        );
+    }
+
+    #[test]
+    fn floyd_steinberg_gradient() {
+        let mut ditherer = Ditherer::new(DitherAlgorithm::FloydSteinberg);
+        assert_eq!(
+            render(&mut ditherer, 0..4, gradient),
+            ["###.#...", "##.#..#.", "###.#...", "##.#.#.."]
+        );
+    }
+
+    #[test]
+    fn atkinson_gradient() {
+        let mut ditherer = Ditherer::new(DitherAlgorithm::Atkinson);
+        assert_eq!(
+            render(&mut ditherer, 0..4, gradient),
+            ["###.....", "#####...", "##..#...", "###....."]
+        );
+    }
+
+    #[test]
+    fn error_dropped_when_rows_are_skipped() {
+        let mut ditherer = Ditherer::new(DitherAlgorithm::FloydSteinberg);
+        assert_eq!(render(&mut ditherer, 0..1, |_, _| 96), ["#.##.##."]);
+        // the next row picks up the error
+        assert_eq!(render(&mut ditherer, 1..2, |_, _| 96), ["##.##.##"]);
+        // a row further down starts over, like the first one
+        assert_eq!(render(&mut ditherer, 5..6, |_, _| 96), ["#.##.##."]);
+    }
+}
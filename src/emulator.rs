@@ -0,0 +1,440 @@
+use core::{cell::RefCell, convert::Infallible, marker::PhantomData};
+
+use crate::{
+    pbm, CdiFlags, FlgFlags, OutOfBounds, PsrFlags, Register, Res128x296, Resolution, Uc8151,
+};
+use embedded_hal_1::digital::{self, InputPin, OutputPin};
+use embedded_hal_async::{
+    digital::Wait,
+    spi::{self, Operation, SpiDevice},
+};
+
+/// RAM size for the largest supported resolution, 160x296.
+const RAM: usize = 160 * 296 / 8;
+
+/// Color of a pixel on the emulated panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PanelColor {
+    #[default]
+    White,
+    Black,
+    Red,
+}
+
+/// Software model of the UC8151, fed with the bytes the driver sends.
+///
+/// Tracks the panel setting, resolution and partial window, fills the
+/// `DTM1`/`DTM2` RAM and copies it onto the emulated panel on refresh.
+/// Waveforms are not simulated: in black and white mode the panel shows the
+/// new data, so the gray levels of [`Uc8151::update_gray`] come out as two
+/// colors.
+///
+/// Connect it to a [`Uc8151`] with [`Emulator::uc8151`]:
+///
+/// ```ignore
+/// let emulator = RefCell::new(Emulator::new());
+/// let mut display = Display::new(Emulator::uc8151(&emulator)).await?;
+/// // draw and push
+/// let len = emulator.borrow().to_pbm(&mut buffer)?;
+/// ```
+pub struct Emulator {
+    /// Level of the data/command pin, high for data.
+    dc: bool,
+    command: Option<u8>,
+    /// Data bytes received since the last command.
+    index: usize,
+    psr: u8,
+    /// Gate and source lines set by `TRES`.
+    resolution: Option<(usize, usize)>,
+    cdi: u8,
+    powered: bool,
+    sleeping: bool,
+    partial: bool,
+    window: [u8; 7],
+    old: [u8; RAM],
+    new: [u8; RAM],
+    black: [u8; RAM],
+    red: [u8; RAM],
+    refreshes: usize,
+    temperature: i16,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emulator {
+    pub fn new() -> Self {
+        Self {
+            dc: false,
+            command: None,
+            index: 0,
+            psr: 0x0f,
+            resolution: None,
+            cdi: 0,
+            powered: false,
+            sleeping: false,
+            partial: false,
+            window: [0; 7],
+            old: [0; RAM],
+            new: [0; RAM],
+            black: [0; RAM],
+            red: [0; RAM],
+            refreshes: 0,
+            temperature: 20 << 3,
+        }
+    }
+
+    /// Driver for the emulator, with the bus and pins borrowing `emulator`.
+    ///
+    /// Register reads are enabled, the emulator answers them.
+    pub fn uc8151<R: Resolution>(emulator: &RefCell<Emulator>) -> EmulatedUc8151<'_, R> {
+        let mut uc8151 = Uc8151::new(
+            EmulatorSpi(emulator),
+            EmulatorDc(emulator),
+            EmulatorReset(emulator),
+            EmulatorBusy(PhantomData),
+        );
+        uc8151.set_readable(true);
+        uc8151
+    }
+
+    /// Number of gate lines, the width of the framebuffer.
+    pub fn width(&self) -> usize {
+        self.geometry().0
+    }
+
+    /// Number of source lines, the height of the framebuffer.
+    pub fn height(&self) -> usize {
+        self.geometry().1
+    }
+
+    fn geometry(&self) -> (usize, usize) {
+        if let Some(resolution) = self.resolution {
+            return resolution;
+        }
+        match PsrFlags::from_bits_retain(self.psr & 0b11000000) {
+            PsrFlags::RES_96x252 => (252, 96),
+            PsrFlags::RES_128x296 => (296, 128),
+            PsrFlags::RES_160x296 => (296, 160),
+            _ => (230, 96),
+        }
+    }
+
+    /// Number of refreshes since creation.
+    pub fn refreshes(&self) -> usize {
+        self.refreshes
+    }
+
+    /// Whether the controller is in deep sleep.
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Temperature reported to the driver, in steps of 1/8 °C.
+    pub fn set_temperature(&mut self, temperature: i16) {
+        self.temperature = temperature;
+    }
+
+    /// Color shown at `x`, `y` in framebuffer coordinates.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<PanelColor> {
+        let (width, height) = self.geometry();
+        if x >= width || y >= height {
+            return None;
+        }
+        let address = x * (height / 8) + y / 8;
+        let mask = 0x80 >> (y % 8);
+        let color = if self.red[address] & mask != 0 {
+            PanelColor::Red
+        } else if self.black[address] & mask != 0 {
+            PanelColor::Black
+        } else {
+            PanelColor::White
+        };
+        Some(color)
+    }
+
+    /// Render the panel as binary PBM into `out`, returning its length.
+    ///
+    /// Red pixels come out black.
+    pub fn to_pbm(&self, out: &mut [u8]) -> Result<usize, OutOfBounds> {
+        pbm::encode(self.width(), self.height(), out, |x, y| {
+            self.pixel(x, y) != Some(PanelColor::White)
+        })
+    }
+
+    fn reset(&mut self) {
+        let panel = (self.black, self.red, self.refreshes, self.temperature);
+        *self = Self::new();
+        // the image stays on the panel and the RAM contents are undefined
+        (self.black, self.red, self.refreshes, self.temperature) = panel;
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if self.dc {
+                self.data(byte);
+            } else {
+                self.command(byte);
+            }
+        }
+    }
+
+    fn command(&mut self, register: u8) {
+        if self.sleeping {
+            return;
+        }
+        self.command = Some(register);
+        self.index = 0;
+
+        match register {
+            r if r == Register::PON as u8 => self.powered = true,
+            r if r == Register::POF as u8 => self.powered = false,
+            r if r == Register::PTIN as u8 => self.partial = true,
+            r if r == Register::PTOU as u8 => self.partial = false,
+            r if r == Register::DRF as u8 => self.refresh(),
+            _ => {}
+        }
+    }
+
+    fn data(&mut self, byte: u8) {
+        let Some(register) = self.command.filter(|_| !self.sleeping) else {
+            return;
+        };
+        let index = self.index;
+        self.index += 1;
+
+        match register {
+            r if r == Register::PSR as u8 && index == 0 => self.psr = byte,
+            r if r == Register::TRES as u8 => {
+                let (mut gates, mut sources) = self.resolution.unwrap_or(self.geometry());
+                match index {
+                    0 => sources = byte as usize & !0b111,
+                    1 => gates = (byte as usize & 0b1) << 8 | (gates & 0xff),
+                    2 => gates = (gates & 0x100) | byte as usize,
+                    _ => return,
+                }
+                // limited to the largest supported panel
+                self.resolution = Some((gates.min(296), sources.min(160)));
+            }
+            r if r == Register::CDI as u8 && index == 0 => self.cdi = byte,
+            r if r == Register::PTL as u8 && index < 7 => self.window[index] = byte,
+            r if r == Register::DSLP as u8 && byte == 0xa5 => self.sleeping = true,
+            r if r == Register::DTM1 as u8 => {
+                if let Some(address) = self.address(index) {
+                    self.old[address] = byte;
+                }
+            }
+            r if r == Register::DTM2 as u8 => {
+                if let Some(address) = self.address(index) {
+                    self.new[address] = byte;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        let mut answer = [0xff; 4];
+        match self.command {
+            Some(r) if r == Register::REV as u8 => answer = [0x00, 0x00, 0x00, 0x0c],
+            Some(r) if r == Register::FLG as u8 => {
+                let mut flags = FlgFlags::BUSY_N;
+                flags.set(FlgFlags::PON, self.powered);
+                flags.set(FlgFlags::POF, !self.powered);
+                flags.set(FlgFlags::PTL, self.partial);
+                answer[0] = flags.bits();
+            }
+            Some(r) if r == Register::TSC as u8 => {
+                answer[..2].copy_from_slice(&(self.temperature << 5).to_be_bytes())
+            }
+            Some(r) if r == Register::LPD as u8 => answer[0] = 0b1,
+            Some(r) if r == Register::VV as u8 => answer[0] = 0b011110,
+            _ => {}
+        }
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte = answer.get(i).copied().unwrap_or(0xff);
+        }
+    }
+
+    /// Partial window as first and last gate and source line.
+    fn window(&self) -> (usize, usize, usize, usize) {
+        let w = self.window.map(usize::from);
+        let (width, height) = self.geometry();
+        if !self.partial {
+            return (0, width - 1, 0, height - 1);
+        }
+        let source_start = w[0] & !0b111;
+        let source_end = w[1] | 0b111;
+        let gate_start = (w[2] & 0b1) << 8 | w[3];
+        let gate_end = (w[4] & 0b1) << 8 | w[5];
+        (
+            gate_start.min(width - 1),
+            gate_end.min(width - 1),
+            source_start.min(height - 1),
+            source_end.min(height - 1),
+        )
+    }
+
+    /// RAM address of the `index`th data byte, walking the partial window.
+    fn address(&self, index: usize) -> Option<usize> {
+        let (_, height) = self.geometry();
+        let (gate_start, gate_end, source_start, source_end) = self.window();
+        let column = (source_end + 1).saturating_sub(source_start) / 8;
+        if column == 0 {
+            return None;
+        }
+        let gate = gate_start + index / column;
+        if gate > gate_end {
+            return None;
+        }
+        let address = gate * (height / 8) + source_start / 8 + index % column;
+        (address < RAM).then_some(address)
+    }
+
+    fn refresh(&mut self) {
+        if !self.powered {
+            return;
+        }
+        self.refreshes += 1;
+
+        let (_, height) = self.geometry();
+        let (gate_start, gate_end, source_start, source_end) = self.window();
+        let polarity = if self.cdi & CdiFlags::POLARITY_INVERTED.bits() != 0 {
+            0xff
+        } else {
+            0x00
+        };
+        let black_white = self.psr & PsrFlags::FORMAT_BW.bits() != 0;
+
+        for gate in gate_start..=gate_end {
+            let column = gate * (height / 8);
+            for address in column + source_start / 8..=column + source_end / 8 {
+                if black_white {
+                    self.black[address] = self.new[address] ^ polarity;
+                    self.red[address] = 0;
+                    // the new data becomes the old data of the next refresh
+                    self.old[address] = self.new[address];
+                } else {
+                    // cleared bits are colored in black/white/red mode
+                    self.black[address] = !self.old[address] ^ polarity;
+                    self.red[address] = !self.new[address] ^ polarity;
+                }
+            }
+        }
+    }
+}
+
+/// [`Uc8151`] driving an [`Emulator`].
+pub type EmulatedUc8151<'a, R = Res128x296> =
+    Uc8151<EmulatorSpi<'a>, EmulatorDc<'a>, EmulatorReset<'a>, EmulatorBusy<'a>, R>;
+
+/// SPI device of an [`Emulator`].
+pub struct EmulatorSpi<'a>(&'a RefCell<Emulator>);
+
+impl spi::ErrorType for EmulatorSpi<'_> {
+    type Error = Infallible;
+}
+
+impl SpiDevice for EmulatorSpi<'_> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        let mut emulator = self.0.borrow_mut();
+        for operation in operations {
+            match operation {
+                Operation::Read(buffer) => emulator.read(buffer),
+                Operation::Write(bytes) => emulator.write(bytes),
+                Operation::Transfer(read, write) => {
+                    emulator.write(write);
+                    emulator.read(read);
+                }
+                Operation::TransferInPlace(buffer) => {
+                    emulator.write(buffer);
+                    emulator.read(buffer);
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Data/command pin of an [`Emulator`].
+pub struct EmulatorDc<'a>(&'a RefCell<Emulator>);
+
+impl digital::ErrorType for EmulatorDc<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for EmulatorDc<'_> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().dc = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().dc = true;
+        Ok(())
+    }
+}
+
+/// Reset pin of an [`Emulator`], resets it when driven low.
+pub struct EmulatorReset<'a>(&'a RefCell<Emulator>);
+
+impl digital::ErrorType for EmulatorReset<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for EmulatorReset<'_> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().reset();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Busy pin of an [`Emulator`], which finishes everything instantly.
+pub struct EmulatorBusy<'a>(PhantomData<&'a RefCell<Emulator>>);
+
+impl digital::ErrorType for EmulatorBusy<'_> {
+    type Error = Infallible;
+}
+
+impl InputPin for EmulatorBusy<'_> {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(true)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+}
+
+impl Wait for EmulatorBusy<'_> {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+/// Errors while talking to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// SPI transfer failed.
+    Spi(E),
+    /// A control pin could not be driven or read.
+    Pin,
+    /// The panel did not release the busy line in time, usually a loose cable.
+    BusyTimeout,
+    /// Drawing or update outside of the framebuffer.
+    OutOfBounds,
+    /// A refresh was requested while the previous one is still running.
+    RefreshInProgress,
+    /// The refresh needs another [`ColorFormat`](crate::ColorFormat) than the
+    /// selected one.
+    WrongFormat,
+    /// Registers can't be read, see
+    /// [`Uc8151::set_readable`](crate::Uc8151::set_readable).
+    ReadUnsupported,
+}
+
+/// Pixel or region outside of the framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds;
+
+impl<E> From<OutOfBounds> for Error<E> {
+    fn from(_: OutOfBounds) -> Self {
+        Error::OutOfBounds
+    }
+}
+
+/// Image data which can't be decoded, or doesn't match the framebuffer size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidImage;
@@ -0,0 +1,476 @@
+use core::{cmp::Ordering, convert::Infallible};
+
+use crate::{OutOfBounds, Res128x296, Resolution};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Point, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// How source pixels are combined with the pixels already in the framebuffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Overwrite destination with source.
+    #[default]
+    Replace,
+    /// Set pixels which are set in either.
+    Or,
+    /// Keep only pixels which are set in both.
+    And,
+    /// Toggle pixels which are set in the source.
+    Xor,
+}
+
+impl BlendMode {
+    /// Combine destination and source bits (works on single pixels and whole bytes).
+    pub fn apply(self, destination: u8, source: u8) -> u8 {
+        match self {
+            BlendMode::Replace => source,
+            BlendMode::Or => destination | source,
+            BlendMode::And => destination & source,
+            BlendMode::Xor => destination ^ source,
+        }
+    }
+}
+
+impl<R: Resolution> Default for Framebuffer<R> {
+    fn default() -> Self {
+        Self { bits: R::EMPTY }
+    }
+}
+
+/// 1bpp framebuffer in the layout of the panel memory.
+///
+/// Pixels are stored column by column, each column is `HEIGHT / 8` bytes with
+/// the topmost pixel in the most significant bit.
+pub struct Framebuffer<R: Resolution = Res128x296> {
+    bits: R::Buffer,
+}
+
+impl<R: Resolution> Framebuffer<R> {
+    pub const HEIGHT: usize = R::HEIGHT;
+    pub const WIDTH: usize = R::WIDTH;
+
+    /// Bytes per column.
+    const STRIDE: usize = Self::HEIGHT / 8;
+
+    pub fn write(&mut self, x: usize, y: usize, value: bool) -> Result<(), OutOfBounds> {
+        if x >= Self::WIDTH || y >= Self::HEIGHT {
+            return Err(OutOfBounds);
+        }
+
+        self.set(x, y, value);
+        Ok(())
+    }
+
+    /// Write a pixel which is known to be in bounds.
+    fn set(&mut self, x: usize, y: usize, value: bool) {
+        let address = (y / 8) + (x * Self::STRIDE);
+
+        let o: u8 = 7 - (y as u8 & 0b111); // bit offset within byte
+        let m: u8 = !(1 << o); // bit mask for byte
+        let b: u8 = (value as u8) << o; // bit value shifted to position
+        let bits = self.bits.as_mut();
+        bits[address] = (bits[address] & m) | b;
+    }
+
+    /// Read back a pixel, `None` if it is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<bool> {
+        if x >= Self::WIDTH || y >= Self::HEIGHT {
+            return None;
+        }
+
+        let address = (y / 8) + (x * Self::STRIDE);
+        let o: u8 = 7 - (y as u8 & 0b111);
+        Some(self.bits.as_ref()[address] & (1 << o) != 0)
+    }
+
+    /// Raw framebuffer contents, in panel layout.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bits.as_ref()
+    }
+
+    /// Mutable raw framebuffer contents, in panel layout.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.bits.as_mut()
+    }
+
+    /// Set all pixels to `value`.
+    pub fn fill(&mut self, value: bool) {
+        self.bits.as_mut().fill(if value { 0xff } else { 0x00 });
+    }
+
+    /// Set all pixels inside `area` to `value`, clipped to the framebuffer.
+    ///
+    /// Works on whole bytes of each column where possible.
+    pub fn fill_rect(&mut self, area: Rectangle, value: bool) {
+        let bounds = Rectangle::new(
+            Point::zero(),
+            Size::new(Self::WIDTH as u32, Self::HEIGHT as u32),
+        );
+        let area = area.intersection(&bounds);
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+
+        let fill = if value { 0xff } else { 0x00 };
+        let top = area.top_left.y as usize;
+        let bottom = bottom_right.y as usize + 1;
+        for x in area.top_left.x as usize..=bottom_right.x as usize {
+            let column = &mut self.bits.as_mut()[x * Self::STRIDE..(x + 1) * Self::STRIDE];
+            for (i, byte) in column
+                .iter_mut()
+                .enumerate()
+                .take(bottom.div_ceil(8))
+                .skip(top / 8)
+            {
+                // rows of this byte covered by the area, counted from the msb
+                let start = top.max(i * 8) - i * 8;
+                let end = bottom.min(i * 8 + 8) - i * 8;
+                let mask = (0xff >> start) & (0xff << (8 - end));
+                *byte = (*byte & !mask) | (fill & mask);
+            }
+        }
+    }
+
+    /// Toggle all pixels.
+    pub fn invert(&mut self) {
+        self.bits
+            .as_mut()
+            .iter_mut()
+            .for_each(|byte| *byte = !*byte);
+    }
+
+    /// Combine a 1bpp image into the framebuffer at `x`, `y`.
+    ///
+    /// The image is stored row by row, each row padded to whole bytes with
+    /// the leftmost pixel in the most significant bit. Pixels falling outside
+    /// the framebuffer are skipped.
+    pub fn blit(&mut self, x: usize, y: usize, width: usize, data: &[u8], mode: BlendMode) {
+        let row_bytes = width.div_ceil(8);
+        if row_bytes == 0 {
+            return;
+        }
+
+        for (row, line) in data.chunks_exact(row_bytes).enumerate() {
+            for column in 0..width {
+                let source = line[column / 8] >> (7 - (column & 0b111)) & 1;
+                let Some(destination) = self.get(x + column, y + row) else {
+                    continue;
+                };
+                let value = mode.apply(destination as u8, source) & 1;
+                self.set(x + column, y + row, value != 0);
+            }
+        }
+    }
+
+    /// Shift the contents by `dx`, `dy` pixels, filling uncovered pixels with `fill`.
+    pub fn scroll(&mut self, dx: i32, dy: i32, fill: bool) {
+        let fill = if fill { 0xff } else { 0x00 };
+
+        let columns = (dx.unsigned_abs() as usize).min(Self::WIDTH);
+        let shift = columns * Self::STRIDE;
+        let bits = self.bits.as_mut();
+        let len = bits.len();
+        match dx.cmp(&0) {
+            Ordering::Greater => {
+                bits.copy_within(0..len - shift, shift);
+                bits[..shift].fill(fill);
+            }
+            Ordering::Less => {
+                bits.copy_within(shift.., 0);
+                bits[len - shift..].fill(fill);
+            }
+            Ordering::Equal => {}
+        }
+
+        let rows = (dy.unsigned_abs() as usize).min(Self::HEIGHT);
+        if rows == 0 {
+            return;
+        }
+        for column in bits.chunks_exact_mut(Self::STRIDE) {
+            if dy > 0 {
+                shift_down(column, rows, fill);
+            } else {
+                shift_up(column, rows, fill);
+            }
+        }
+    }
+
+    /// Copy the pixels in `area` so that its top left corner ends up at `to`.
+    ///
+    /// Source and destination may overlap. Pixels outside the framebuffer are
+    /// skipped.
+    pub fn copy_region(&mut self, area: Rectangle, to: Point) {
+        let offset = to - area.top_left;
+        let width = area.size.width as i32;
+        let height = area.size.height as i32;
+
+        // walk away from the destination so overlapping pixels are read before
+        // they are overwritten
+        for i in 0..width {
+            let dx = if offset.x > 0 { width - 1 - i } else { i };
+            for j in 0..height {
+                let dy = if offset.y > 0 { height - 1 - j } else { j };
+                let source = area.top_left + Point::new(dx, dy);
+                let destination = source + offset;
+                if destination.x < 0 || destination.y < 0 || source.x < 0 || source.y < 0 {
+                    continue;
+                }
+                if let Some(value) = self.get(source.x as usize, source.y as usize) {
+                    if (destination.x as usize) < Self::WIDTH
+                        && (destination.y as usize) < Self::HEIGHT
+                    {
+                        self.set(destination.x as usize, destination.y as usize, value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<R: Resolution> Framebuffer<R> {
+    /// Diagonal stripes with every fifth pixel black, so misplaced bytes
+    /// show. Fixture shared by the tests.
+    #[doc(hidden)]
+    pub fn stripes() -> Self {
+        let mut framebuffer = Self::default();
+        for x in 0..Self::WIDTH {
+            for y in 0..Self::HEIGHT {
+                let _ = framebuffer.write(x, y, (x * 7 + y * 3) % 5 == 0);
+            }
+        }
+        framebuffer
+    }
+}
+
+/// Draws without rotation, with black as [`BinaryColor::Off`] like
+/// [`Display`](crate::Display). Pixels outside the framebuffer are skipped.
+impl<R: Resolution> DrawTarget for Framebuffer<R> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(pos, color) in pixels {
+            if pos.x >= 0 && pos.y >= 0 {
+                let _ = self.write(pos.x as _, pos.y as _, color == BinaryColor::Off);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_rect(*area, color == BinaryColor::Off);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(color == BinaryColor::Off);
+        Ok(())
+    }
+}
+
+impl<R: Resolution> OriginDimensions for Framebuffer<R> {
+    fn size(&self) -> Size {
+        Size::new(R::WIDTH as u32, R::HEIGHT as u32)
+    }
+}
+
+/// Shift a column towards higher rows by `n` pixels.
+fn shift_down(column: &mut [u8], n: usize, fill: u8) {
+    let bytes = n / 8;
+    let bits = n % 8;
+    let len = column.len();
+    if bytes >= len {
+        column.fill(fill);
+        return;
+    }
+
+    column.copy_within(0..len - bytes, bytes);
+    column[..bytes].fill(fill);
+
+    if bits > 0 {
+        let mut carry = fill << (8 - bits);
+        for byte in column.iter_mut() {
+            let next = *byte << (8 - bits);
+            *byte = (*byte >> bits) | carry;
+            carry = next;
+        }
+    }
+}
+
+/// Shift a column towards lower rows by `n` pixels.
+fn shift_up(column: &mut [u8], n: usize, fill: u8) {
+    let bytes = n / 8;
+    let bits = n % 8;
+    let len = column.len();
+    if bytes >= len {
+        column.fill(fill);
+        return;
+    }
+
+    column.copy_within(bytes.., 0);
+    column[len - bytes..].fill(fill);
+
+    if bits > 0 {
+        let mut carry = fill >> (8 - bits);
+        for byte in column.iter_mut().rev() {
+            let next = *byte >> (8 - bits);
+            *byte = (*byte << bits) | carry;
+            carry = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    type Fb = Framebuffer<Res128x296>;
+
+    /// Framebuffer filled with a fixed pseudo random pattern.
+    fn pattern() -> Fb {
+        let mut framebuffer = Fb::default();
+        let mut state = 0x2545_f491_u32;
+        for byte in framebuffer.as_bytes_mut() {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            *byte = (state >> 16) as u8;
+        }
+        framebuffer
+    }
+
+    fn pixels(framebuffer: &Fb) -> Vec<bool> {
+        (0..Fb::WIDTH)
+            .flat_map(|x| (0..Fb::HEIGHT).map(move |y| framebuffer.get(x, y).unwrap()))
+            .collect()
+    }
+
+    /// Expected pixels from a per pixel function of the coordinates.
+    fn expected(pixel: impl Fn(usize, usize) -> bool) -> Vec<bool> {
+        (0..Fb::WIDTH)
+            .flat_map(|x| (0..Fb::HEIGHT).map(move |y| (x, y)))
+            .map(|(x, y)| pixel(x, y))
+            .collect()
+    }
+
+    #[test]
+    fn scroll_matches_per_pixel_shift() {
+        let shifts = [
+            (1, 0),
+            (-1, 0),
+            (9, 0),
+            (-9, 0),
+            (0, 1),
+            (0, -1),
+            (0, 9),
+            (0, -9),
+            (7, -9),
+            (-9, 1),
+            (400, 0),
+            (0, -200),
+            (-300, 130),
+        ];
+        for (dx, dy) in shifts {
+            for fill in [false, true] {
+                let original = pattern();
+                let mut framebuffer = pattern();
+                framebuffer.scroll(dx, dy, fill);
+                let want = expected(|x, y| {
+                    let source_x = x as i32 - dx;
+                    let source_y = y as i32 - dy;
+                    if source_x < 0 || source_y < 0 {
+                        return fill;
+                    }
+                    original
+                        .get(source_x as usize, source_y as usize)
+                        .unwrap_or(fill)
+                });
+                assert!(
+                    pixels(&framebuffer) == want,
+                    "scroll by {}, {} with fill {}",
+                    dx,
+                    dy,
+                    fill
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn copy_region_handles_overlap_in_all_directions() {
+        let area = Rectangle::new(Point::new(20, 13), Size::new(40, 30));
+        let offsets = [
+            Point::new(5, 3),
+            Point::new(-5, 3),
+            Point::new(5, -3),
+            Point::new(-5, -3),
+            Point::new(0, 9),
+            Point::new(-30, -20),
+        ];
+        for offset in offsets {
+            let original = pattern();
+            let mut framebuffer = pattern();
+            framebuffer.copy_region(area, area.top_left + offset);
+            let want = expected(|x, y| {
+                let source = Point::new(x as i32, y as i32) - offset;
+                if area.contains(source) {
+                    original.get(source.x as usize, source.y as usize).unwrap()
+                } else {
+                    original.get(x, y).unwrap()
+                }
+            });
+            assert!(pixels(&framebuffer) == want, "copy by {:?}", offset);
+        }
+    }
+
+    #[test]
+    fn fill_rect_with_unaligned_rows() {
+        let areas = [
+            Rectangle::new(Point::new(3, 5), Size::new(10, 13)),
+            Rectangle::new(Point::new(0, 2), Size::new(1, 3)),
+            Rectangle::new(Point::new(290, 121), Size::new(20, 20)),
+            Rectangle::new(Point::new(-4, -3), Size::new(8, 12)),
+        ];
+        for area in areas {
+            for value in [false, true] {
+                let original = pattern();
+                let mut framebuffer = pattern();
+                framebuffer.fill_rect(area, value);
+                let want = expected(|x, y| {
+                    if area.contains(Point::new(x as i32, y as i32)) {
+                        value
+                    } else {
+                        original.get(x, y).unwrap()
+                    }
+                });
+                assert!(pixels(&framebuffer) == want, "fill {:?}", area);
+            }
+        }
+    }
+
+    #[test]
+    fn blit_combines_unaligned_rows() {
+        // 10x2 image, the second row only has its last pixel set
+        let data = [0b1010_0000, 0b0100_0000, 0b0000_0000, 0b0100_0000];
+        let original = pattern();
+        let mut framebuffer = pattern();
+        framebuffer.blit(290, 126, 10, &data, BlendMode::Xor);
+        let want = expected(|x, y| {
+            let image = match (x.checked_sub(290), y.checked_sub(126)) {
+                (Some(column), Some(row)) if column < 10 && row < 2 => {
+                    data[row * 2 + column / 8] & (0x80 >> (column % 8)) != 0
+                }
+                _ => false,
+            };
+            original.get(x, y).unwrap() ^ image
+        });
+        assert!(pixels(&framebuffer) == want);
+    }
+}
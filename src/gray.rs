@@ -0,0 +1,143 @@
+use crate::{ColorFormat, Error, Framebuffer, OutOfBounds, Res128x296, Resolution, Uc8151};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::{Gray2, GrayColor},
+    Pixel,
+};
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, spi::SpiDevice};
+
+/// Framebuffer with two bits of gray per pixel, split into two planes.
+///
+/// Set bits are darker: both set is black, both cleared is white.
+pub struct GrayFramebuffer<R: Resolution = Res128x296> {
+    high: Framebuffer<R>,
+    low: Framebuffer<R>,
+}
+
+impl<R: Resolution> Default for GrayFramebuffer<R> {
+    fn default() -> Self {
+        Self {
+            high: Framebuffer::default(),
+            low: Framebuffer::default(),
+        }
+    }
+}
+
+impl<R: Resolution> GrayFramebuffer<R> {
+    pub fn write(&mut self, x: usize, y: usize, color: Gray2) -> Result<(), OutOfBounds> {
+        let ink = 3 - color.luma();
+        self.high.write(x, y, ink & 0b10 != 0)?;
+        self.low.write(x, y, ink & 0b01 != 0)
+    }
+
+    /// Read back a pixel, `None` if it is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<Gray2> {
+        let ink = (self.high.get(x, y)? as u8) << 1 | self.low.get(x, y)? as u8;
+        Some(Gray2::new(3 - ink))
+    }
+
+    /// Set all pixels to `color`.
+    pub fn fill(&mut self, color: Gray2) {
+        let ink = 3 - color.luma();
+        self.high.fill(ink & 0b10 != 0);
+        self.low.fill(ink & 0b01 != 0);
+    }
+}
+
+/// Display showing four levels of gray.
+///
+/// Draw [`Gray8`](embedded_graphics::pixelcolor::Gray8) content through
+/// [`DrawTarget::color_converted`] to have it quantized to four levels.
+/// Refreshing takes two passes and is slower than [`Display`](crate::Display).
+pub struct GrayDisplay<SPI, DC, RESET, BUSY, R: Resolution = Res128x296> {
+    uc8151: Uc8151<SPI, DC, RESET, BUSY, R>,
+    framebuffer: GrayFramebuffer<R>,
+}
+
+impl<SPI, DC, RESET, BUSY, R> GrayDisplay<SPI, DC, RESET, BUSY, R>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
+    /// Initialize display from Uc8151, switching it to
+    /// [`ColorFormat::BlackWhite`].
+    ///
+    /// The controller is initialized with the settings of its last
+    /// [`Uc8151::init`], or the defaults.
+    pub async fn new(
+        mut uc8151: Uc8151<SPI, DC, RESET, BUSY, R>,
+    ) -> Result<Self, Error<SPI::Error>> {
+        uc8151.set_format(ColorFormat::BlackWhite);
+        uc8151.init(uc8151.config()).await?;
+        Ok(Self {
+            uc8151,
+            framebuffer: GrayFramebuffer::default(),
+        })
+    }
+
+    /// Write framebuffer to display and refresh.
+    pub async fn push_to_display(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.uc8151
+            .update_gray(
+                self.framebuffer.high.as_bytes(),
+                self.framebuffer.low.as_bytes(),
+            )
+            .await
+    }
+
+    /// Clear framebuffer - call [Self::push_to_display] to clear display.
+    pub fn clear_buffer(&mut self) {
+        self.framebuffer.fill(Gray2::WHITE);
+    }
+
+    pub fn framebuffer(&self) -> &GrayFramebuffer<R> {
+        &self.framebuffer
+    }
+
+    pub fn framebuffer_mut(&mut self) -> &mut GrayFramebuffer<R> {
+        &mut self.framebuffer
+    }
+}
+
+impl<SPI, DC, RESET, BUSY, R> DrawTarget for GrayDisplay<SPI, DC, RESET, BUSY, R>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
+    type Color = Gray2;
+    type Error = Error<SPI::Error>;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        for Pixel(pos, color) in pixels.into_iter().filter(|Pixel(pos, _)| bb.contains(*pos)) {
+            self.framebuffer.write(pos.x as _, pos.y as _, color)?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.framebuffer.fill(color);
+        Ok(())
+    }
+}
+
+impl<SPI, DC, RESET, BUSY, R: Resolution> OriginDimensions
+    for GrayDisplay<SPI, DC, RESET, BUSY, R>
+{
+    fn size(&self) -> Size {
+        Size::new(R::WIDTH as u32, R::HEIGHT as u32)
+    }
+}
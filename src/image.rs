@@ -0,0 +1,222 @@
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    image::{GetPixel, ImageDrawable},
+    pixelcolor::BinaryColor,
+    primitives::{PointsIter, Rectangle},
+};
+
+/// Order of the pixels within each byte of a [`ImageRaw1bpp`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Leftmost pixel in the most significant bit, like PBM rasters.
+    #[default]
+    MsbFirst,
+    /// Leftmost pixel in the least significant bit, like XBM.
+    LsbFirst,
+}
+
+/// Raw 1bpp image, stored row by row with each row padded to whole bytes.
+///
+/// Set bits are black, drawn as [`BinaryColor::Off`] like on the
+/// [`Framebuffer`](crate::Framebuffer). Use [`include_image!`] to embed
+/// an image file with its size checked at compile time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageRaw1bpp<'a> {
+    data: &'a [u8],
+    size: Size,
+    bit_order: BitOrder,
+}
+
+impl<'a> ImageRaw1bpp<'a> {
+    /// # Panics
+    ///
+    /// If `data` is shorter than [`Self::data_len`] for `width` and `height`.
+    pub const fn new(data: &'a [u8], width: u32, height: u32, bit_order: BitOrder) -> Self {
+        assert!(
+            data.len() >= Self::data_len(width, height),
+            "image data shorter than its dimensions"
+        );
+        Self {
+            data,
+            size: Size::new(width, height),
+            bit_order,
+        }
+    }
+
+    /// Bytes needed for an image of `width` x `height` pixels.
+    pub const fn data_len(width: u32, height: u32) -> usize {
+        (width as usize).div_ceil(8) * height as usize
+    }
+
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Whether the pixel at `x`, `y` is black, without bounds check.
+    fn black(&self, x: usize, y: usize) -> bool {
+        let byte = self.data[y * (self.size.width as usize).div_ceil(8) + x / 8];
+        let mask = match self.bit_order {
+            BitOrder::MsbFirst => 0x80 >> (x % 8),
+            BitOrder::LsbFirst => 0x01 << (x % 8),
+        };
+        byte & mask != 0
+    }
+
+    fn color(&self, point: Point) -> BinaryColor {
+        if self.black(point.x as usize, point.y as usize) {
+            BinaryColor::Off
+        } else {
+            BinaryColor::On
+        }
+    }
+}
+
+impl OriginDimensions for ImageRaw1bpp<'_> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl GetPixel for ImageRaw1bpp<'_> {
+    type Color = BinaryColor;
+
+    fn pixel(&self, point: Point) -> Option<Self::Color> {
+        self.bounding_box()
+            .contains(point)
+            .then(|| self.color(point))
+    }
+}
+
+impl ImageDrawable for ImageRaw1bpp<'_> {
+    type Color = BinaryColor;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = self.bounding_box();
+        target.fill_contiguous(&area, area.points().map(|point| self.color(point)))
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = area.intersection(&self.bounding_box());
+        let colors = area.points().map(|point| self.color(point));
+        target.fill_contiguous(&Rectangle::new(Point::zero(), area.size), colors)
+    }
+}
+
+/// Embed a raw 1bpp image file as [`ImageRaw1bpp`], failing to compile if
+/// the file size doesn't match `width` and `height`.
+///
+/// The path is relative to the current file, like [`include_bytes!`]. The
+/// bit order defaults to [`BitOrder::MsbFirst`].
+///
+/// ```ignore
+/// const FERRIS: ImageRaw1bpp = include_image!("../assets/ferris_1bpp.raw", 86, 64);
+/// ```
+#[macro_export]
+macro_rules! include_image {
+    ($path:literal, $width:expr, $height:expr) => {
+        $crate::include_image!($path, $width, $height, $crate::BitOrder::MsbFirst)
+    };
+    ($path:literal, $width:expr, $height:expr, $bit_order:expr) => {{
+        const DATA: &[u8] = include_bytes!($path);
+        const _: () = assert!(
+            DATA.len() == $crate::ImageRaw1bpp::data_len($width, $height),
+            concat!($path, " doesn't match the declared image size")
+        );
+        $crate::ImageRaw1bpp::new(DATA, $width, $height, $bit_order)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Framebuffer, Res128x296};
+    use embedded_graphics::{draw_target::DrawTargetExt, image::Image, Drawable};
+    use std::vec::Vec;
+
+    type Fb = Framebuffer<Res128x296>;
+
+    /// Two rows of 10 pixels, padded to two bytes each.
+    const DATA: [u8; 4] = [0b1100_0000, 0b0000_0001, 0b0000_0001, 0b0100_0000];
+
+    /// Black pixels of a white framebuffer after `draw`, column by column.
+    fn black_pixels(draw: impl FnOnce(&mut Fb)) -> Vec<(usize, usize)> {
+        let mut framebuffer = Fb::default();
+        draw(&mut framebuffer);
+        (0..Fb::WIDTH)
+            .flat_map(|x| (0..Fb::HEIGHT).map(move |y| (x, y)))
+            .filter(|&(x, y)| framebuffer.get(x, y) == Some(true))
+            .collect()
+    }
+
+    #[test]
+    fn bit_orders() {
+        let offset = Point::new(3, 5);
+        let msb_first = ImageRaw1bpp::new(&DATA, 10, 2, BitOrder::MsbFirst);
+        assert_eq!(
+            black_pixels(|framebuffer| {
+                Image::new(&msb_first, offset).draw(framebuffer).unwrap();
+            }),
+            [(3, 5), (4, 5), (10, 6), (12, 6)]
+        );
+
+        let lsb_first = ImageRaw1bpp::new(&DATA, 10, 2, BitOrder::LsbFirst);
+        assert_eq!(
+            black_pixels(|framebuffer| {
+                Image::new(&lsb_first, offset).draw(framebuffer).unwrap();
+            }),
+            [(3, 6), (9, 5), (10, 5), (11, 5)]
+        );
+
+        assert_eq!(msb_first.pixel(Point::new(9, 1)), Some(BinaryColor::Off));
+        assert_eq!(msb_first.pixel(Point::new(8, 1)), Some(BinaryColor::On));
+        assert_eq!(msb_first.pixel(Point::new(10, 1)), None);
+    }
+
+    #[test]
+    fn clipped_sub_image() {
+        let image = ImageRaw1bpp::new(&DATA, 10, 2, BitOrder::MsbFirst);
+        let offset = Point::new(3, 5);
+
+        // cuts off the first and the last column
+        let inner = Rectangle::new(Point::new(1, 0), Size::new(8, 2));
+        assert_eq!(
+            black_pixels(|framebuffer| {
+                image
+                    .draw_sub_image(&mut framebuffer.translated(offset), &inner)
+                    .unwrap();
+            }),
+            [(3, 5), (9, 6)]
+        );
+
+        // reaches past the bottom right corner
+        let corner = Rectangle::new(Point::new(8, 1), Size::new(5, 5));
+        assert_eq!(
+            black_pixels(|framebuffer| {
+                image
+                    .draw_sub_image(&mut framebuffer.translated(offset), &corner)
+                    .unwrap();
+            }),
+            [(4, 5)]
+        );
+    }
+
+    #[test]
+    fn data_len_pads_rows() {
+        assert_eq!(ImageRaw1bpp::data_len(10, 2), 4);
+        assert_eq!(ImageRaw1bpp::data_len(86, 64), 704);
+        assert_eq!(ImageRaw1bpp::data_len(0, 3), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "image data shorter than its dimensions")]
+    fn new_rejects_short_data() {
+        ImageRaw1bpp::new(&DATA[..3], 10, 2, BitOrder::MsbFirst);
+    }
+}
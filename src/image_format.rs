@@ -0,0 +1,55 @@
+//! Checks shared by the tests of the framebuffer image formats.
+
+use crate::{Framebuffer, InvalidImage, OutOfBounds, Res128x296, Res96x230, Resolution};
+use std::{vec, vec::Vec};
+
+pub(crate) type Fb = Framebuffer<Res128x296>;
+
+/// Image format a framebuffer can be encoded to and decoded from.
+pub(crate) trait ImageFormat {
+    /// Encoded length of a framebuffer.
+    fn len<R: Resolution>() -> usize;
+
+    fn encode<R: Resolution>(
+        framebuffer: &Framebuffer<R>,
+        out: &mut [u8],
+    ) -> Result<usize, OutOfBounds>;
+
+    fn decode<R: Resolution>(data: &[u8]) -> Result<Framebuffer<R>, InvalidImage>;
+}
+
+fn encode<F: ImageFormat, R: Resolution>(framebuffer: &Framebuffer<R>) -> Vec<u8> {
+    let mut buffer = vec![0; F::len::<R>()];
+    assert_eq!(F::encode(framebuffer, &mut buffer), Ok(F::len::<R>()));
+    buffer
+}
+
+/// Check that the stripes decode to themselves, returning their encoding.
+pub(crate) fn round_trip<F: ImageFormat>() -> Vec<u8> {
+    let framebuffer = Fb::stripes();
+    let buffer = encode::<F, _>(&framebuffer);
+    let decoded = F::decode::<Res128x296>(&buffer).unwrap();
+    assert_eq!(decoded.as_bytes(), framebuffer.as_bytes());
+    buffer
+}
+
+pub(crate) fn needs_room<F: ImageFormat>() {
+    let mut buffer = vec![0; F::len::<Res128x296>() - 1];
+    assert_eq!(F::encode(&Fb::stripes(), &mut buffer), Err(OutOfBounds));
+}
+
+pub(crate) fn rejects_other_sizes<F: ImageFormat>() {
+    let buffer = encode::<F, _>(&Framebuffer::<Res96x230>::stripes());
+    assert!(matches!(
+        F::decode::<Res128x296>(&buffer),
+        Err(InvalidImage)
+    ));
+}
+
+pub(crate) fn rejects_truncated<F: ImageFormat>() {
+    let buffer = encode::<F, _>(&Fb::stripes());
+    assert!(matches!(
+        F::decode::<Res128x296>(&buffer[..buffer.len() - 1]),
+        Err(InvalidImage)
+    ));
+}
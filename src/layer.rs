@@ -0,0 +1,118 @@
+use crate::{BlendMode, Framebuffer, Res128x296, Resolution};
+
+/// Framebuffer which is combined with the layers below it before a refresh.
+pub struct Layer<R: Resolution = Res128x296> {
+    framebuffer: Framebuffer<R>,
+    mode: BlendMode,
+    visible: bool,
+}
+
+impl<R: Resolution> Default for Layer<R> {
+    /// Empty, visible layer adding its pixels to the ones below.
+    fn default() -> Self {
+        Self::new(BlendMode::Or)
+    }
+}
+
+impl<R: Resolution> Layer<R> {
+    pub fn new(mode: BlendMode) -> Self {
+        Self {
+            framebuffer: Framebuffer::default(),
+            mode,
+            visible: true,
+        }
+    }
+
+    pub fn mode(&self) -> BlendMode {
+        self.mode
+    }
+
+    /// How this layer is combined with the layers below it.
+    pub fn set_mode(&mut self, mode: BlendMode) {
+        self.mode = mode;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Hidden layers keep their contents but are skipped when compositing.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer<R> {
+        &self.framebuffer
+    }
+
+    pub fn framebuffer_mut(&mut self) -> &mut Framebuffer<R> {
+        &mut self.framebuffer
+    }
+}
+
+/// Combine the visible `layers`, bottom first, into `target`.
+///
+/// Compositing starts from a white framebuffer.
+pub fn composite<'a, R: Resolution + 'a>(
+    layers: impl IntoIterator<Item = &'a Layer<R>>,
+    target: &mut Framebuffer<R>,
+) {
+    target.fill(false);
+    for layer in layers.into_iter().filter(|layer| layer.visible) {
+        for (destination, source) in target
+            .as_bytes_mut()
+            .iter_mut()
+            .zip(layer.framebuffer.as_bytes())
+        {
+            *destination = layer.mode.apply(*destination, *source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(mode: BlendMode, byte: u8) -> Layer {
+        let mut layer = Layer::new(mode);
+        layer.framebuffer_mut().as_bytes_mut().fill(byte);
+        layer
+    }
+
+    /// Composite `layers` onto a black target, all bytes are equal.
+    fn composited(layers: &[Layer]) -> u8 {
+        let mut target = Framebuffer::default();
+        target.fill(true);
+        composite(layers, &mut target);
+        let first = target.as_bytes()[0];
+        assert!(target.as_bytes().iter().all(|&byte| byte == first));
+        first
+    }
+
+    #[test]
+    fn blend_modes_apply_bottom_first() {
+        let base = || layer(BlendMode::Replace, 0b1100_1100);
+        let top = |mode| layer(mode, 0b1010_1010);
+        assert_eq!(composited(&[]), 0);
+        assert_eq!(composited(&[base()]), 0b1100_1100);
+        assert_eq!(composited(&[base(), top(BlendMode::Or)]), 0b1110_1110);
+        assert_eq!(composited(&[base(), top(BlendMode::And)]), 0b1000_1000);
+        assert_eq!(composited(&[base(), top(BlendMode::Xor)]), 0b0110_0110);
+        assert_eq!(composited(&[base(), top(BlendMode::Replace)]), 0b1010_1010);
+        assert_eq!(composited(&[top(BlendMode::Replace), base()]), 0b1100_1100);
+    }
+
+    #[test]
+    fn hidden_layers_are_skipped() {
+        let mut layers = [
+            layer(BlendMode::Or, 0b0011_0000),
+            layer(BlendMode::Replace, 0xff),
+        ];
+        layers[1].set_visible(false);
+        assert_eq!(composited(&layers), 0b0011_0000);
+        assert_eq!(layers[1].framebuffer().as_bytes()[0], 0xff);
+
+        layers[1].set_visible(true);
+        assert_eq!(composited(&layers), 0xff);
+    }
+}
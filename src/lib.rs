@@ -1,10 +1,75 @@
 #![no_std]
 
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+mod error;
+pub use error::*;
+
+mod config;
+pub use config::*;
+
 mod uc8151;
 pub use uc8151::*;
 
+mod framebuffer;
+pub use framebuffer::*;
+
+mod layer;
+pub use layer::*;
+
+mod policy;
+pub use policy::*;
+
 mod display;
 pub use display::*;
 
+mod tricolor;
+pub use tricolor::*;
+
+mod gray;
+pub use gray::*;
+
+mod dither;
+pub use dither::*;
+
+mod image;
+pub use image::*;
+
+mod service;
+pub use service::*;
+
+mod badge;
+pub use badge::*;
+
+mod pbm;
+
+mod bmp;
+
+#[cfg(test)]
+mod image_format;
+
+#[cfg(any(test, feature = "std"))]
+mod emulator;
+#[cfg(any(test, feature = "std"))]
+pub use emulator::*;
+
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+pub use snapshot::*;
+
+#[cfg(feature = "simulator")]
+mod simulator;
+#[cfg(feature = "simulator-window")]
+pub use simulator::*;
+
+#[cfg(feature = "rp2040")]
+mod badger_spi;
+#[cfg(feature = "rp2040")]
+pub use badger_spi::*;
+
+#[cfg(feature = "rp2040")]
 mod peripherals;
+#[cfg(feature = "rp2040")]
 pub use peripherals::{init, Peripherals};
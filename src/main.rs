@@ -5,7 +5,7 @@
 #![no_std]
 #![no_main]
 
-use embassy_badger2040::Display;
+use embassy_badger2040::{draw_badge, BadgerDisplay};
 use embassy_executor::Spawner;
 use embassy_rp::{
     bind_interrupts,
@@ -14,20 +14,7 @@ use embassy_rp::{
     usb::{Driver, InterruptHandler},
 };
 use embassy_time::Timer;
-use embedded_graphics::{
-    geometry::{Point, Size},
-    mono_font::{ascii::FONT_9X18_BOLD, MonoTextStyle},
-    pixelcolor::BinaryColor,
-    primitives::{Primitive, PrimitiveStyle, Rectangle},
-    Drawable,
-};
-use embedded_text::{
-    alignment::HorizontalAlignment,
-    style::{HeightMode, TextBoxStyleBuilder},
-    TextBox,
-};
 use log::info;
-use uc8151::WIDTH;
 use {defmt_rtt as _, panic_probe as _};
 
 bind_interrupts!(struct Irqs {
@@ -62,31 +49,12 @@ async fn main(spawner: Spawner) {
     info!("Initialized");
 
     // setup display (device + framebuffer)
-    let mut display = Display::new(p.UC8151).await;
-    display.push_to_display().await;
+    let mut display: BadgerDisplay = BadgerDisplay::new(p.UC8151).await.unwrap();
+    display.push_to_display().await.unwrap();
 
-    // Create text
-    let text = "Hi! I'm Aron.\nDon't talk to\nme about\nEmbedded Rust.";
-    // Note we're setting the Text color to `Off`. The driver is set up to treat Off as Black so that BMPs work as expected.
-    let character_style = MonoTextStyle::new(&FONT_9X18_BOLD, BinaryColor::Off);
-    let textbox_style = TextBoxStyleBuilder::new()
-        .height_mode(HeightMode::FitToText)
-        .alignment(HorizontalAlignment::Center)
-        .vertical_alignment(embedded_text::alignment::VerticalAlignment::Middle)
-        .paragraph_spacing(6)
-        .build();
-    // Bounding box for our text. Fill it with the opposite color so we can read the text.
-    let bounds = Rectangle::new(Point::new(157, 10), Size::new(WIDTH - 157, 0));
-    bounds
-        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
-        .draw(&mut display) // draw to framebuffer
-        .unwrap();
-    // Create the text box and apply styling options.
-    let text_box = TextBox::with_textbox_style(text, bounds, character_style, textbox_style);
-    // Draw the text box.
-    text_box.draw(&mut display).unwrap(); // draw to framebuffer
-                                          // push framebuffer to display
-    display.push_to_display().await;
+    // draw to framebuffer and push it to the display
+    draw_badge(&mut display).unwrap();
+    display.push_to_display().await.unwrap();
 
     info!("Entering loop");
 
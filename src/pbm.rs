@@ -0,0 +1,197 @@
+use core::fmt::Write;
+
+use crate::{Framebuffer, InvalidImage, OutOfBounds, Resolution};
+
+/// Writes formatted text into a byte slice.
+struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        self.buffer
+            .get_mut(self.len..end)
+            .ok_or(core::fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<R: Resolution> Framebuffer<R> {
+    /// Length of the binary PBM written by [`Self::to_pbm`].
+    pub const PBM_LEN: usize = 3
+        + digits(Self::WIDTH)
+        + 1
+        + digits(Self::HEIGHT)
+        + 1
+        + Self::WIDTH.div_ceil(8) * Self::HEIGHT;
+
+    /// Encode as binary PBM (P4) in landscape like [`Rotation::Deg0`] into
+    /// `out`, returning the number of bytes written.
+    ///
+    /// `out` needs at least [`Self::PBM_LEN`] bytes.
+    ///
+    /// [`Rotation::Deg0`]: crate::Rotation::Deg0
+    pub fn to_pbm(&self, out: &mut [u8]) -> Result<usize, OutOfBounds> {
+        encode(Self::WIDTH, Self::HEIGHT, out, |x, y| {
+            self.get(x, y).unwrap_or_default()
+        })
+    }
+
+    /// Decode a binary PBM (P4) image of exactly `WIDTH` x `HEIGHT` pixels.
+    pub fn from_pbm(data: &[u8]) -> Result<Self, InvalidImage> {
+        let (width, height, raster) = decode(data).ok_or(InvalidImage)?;
+        if (width, height) != (Self::WIDTH, Self::HEIGHT) {
+            return Err(InvalidImage);
+        }
+
+        let mut framebuffer = Self::default();
+        for x in 0..width {
+            for y in 0..height {
+                let _ = framebuffer.write(x, y, pixel(raster, width, x, y));
+            }
+        }
+        Ok(framebuffer)
+    }
+}
+
+/// Number of decimal digits of `n`.
+const fn digits(mut n: usize) -> usize {
+    let mut digits = 1;
+    while n >= 10 {
+        n /= 10;
+        digits += 1;
+    }
+    digits
+}
+
+/// Encode a binary PBM (P4) image into `out`, returning the number of bytes
+/// written. `pixel` returns whether the pixel at `x`, `y` is black.
+pub(crate) fn encode(
+    width: usize,
+    height: usize,
+    out: &mut [u8],
+    pixel: impl Fn(usize, usize) -> bool,
+) -> Result<usize, OutOfBounds> {
+    let mut writer = SliceWriter {
+        buffer: out,
+        len: 0,
+    };
+    write!(writer, "P4\n{} {}\n", width, height).map_err(|_| OutOfBounds)?;
+    let header = writer.len;
+
+    let row_bytes = width.div_ceil(8);
+    let len = header + row_bytes * height;
+    let data = out.get_mut(header..len).ok_or(OutOfBounds)?;
+    data.fill(0);
+    for y in 0..height {
+        for x in 0..width {
+            if pixel(x, y) {
+                data[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+    Ok(len)
+}
+
+/// Parse a binary PBM (P4) image into its width, height and raster.
+///
+/// The raster holds the rows top to bottom, padded to whole bytes, with the
+/// leftmost pixel in the most significant bit and set bits black.
+pub(crate) fn decode(data: &[u8]) -> Option<(usize, usize, &[u8])> {
+    let rest = data.strip_prefix(b"P4")?;
+    let (width, rest) = number(rest)?;
+    let (height, rest) = number(rest)?;
+    // exactly one whitespace byte separates the header from the raster
+    let (separator, raster) = rest.split_first()?;
+    if !separator.is_ascii_whitespace() {
+        return None;
+    }
+    // the size comes from the file, don't trust it
+    let raster = raster.get(..width.div_ceil(8).checked_mul(height)?)?;
+    Some((width, height, raster))
+}
+
+/// Whether the pixel at `x`, `y` of a PBM raster is black.
+pub(crate) fn pixel(raster: &[u8], width: usize, x: usize, y: usize) -> bool {
+    raster[y * width.div_ceil(8) + x / 8] & (0x80 >> (x % 8)) != 0
+}
+
+/// Parse a decimal number preceded by whitespace and comments.
+fn number(mut data: &[u8]) -> Option<(usize, &[u8])> {
+    loop {
+        match data.first()? {
+            b'#' => {
+                let end = data.iter().position(|&byte| byte == b'\n')?;
+                data = &data[end..];
+            }
+            byte if byte.is_ascii_whitespace() => data = &data[1..],
+            _ => break,
+        }
+    }
+    let digits = data.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let value = core::str::from_utf8(&data[..digits]).ok()?.parse().ok()?;
+    Some((value, &data[digits..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_format::{self, Fb, ImageFormat};
+
+    struct Pbm;
+
+    impl ImageFormat for Pbm {
+        fn len<R: Resolution>() -> usize {
+            Framebuffer::<R>::PBM_LEN
+        }
+
+        fn encode<R: Resolution>(
+            framebuffer: &Framebuffer<R>,
+            out: &mut [u8],
+        ) -> Result<usize, OutOfBounds> {
+            framebuffer.to_pbm(out)
+        }
+
+        fn decode<R: Resolution>(data: &[u8]) -> Result<Framebuffer<R>, InvalidImage> {
+            Framebuffer::from_pbm(data)
+        }
+    }
+
+    #[test]
+    fn pbm_round_trip() {
+        let buffer = image_format::round_trip::<Pbm>();
+        // rows of the landscape image, leftmost pixel in the msb
+        assert!(buffer.starts_with(b"P4\n296 128\n"));
+        assert_eq!(buffer[11], 0b1000_0100);
+    }
+
+    #[test]
+    fn to_pbm_needs_room() {
+        image_format::needs_room::<Pbm>();
+    }
+
+    #[test]
+    fn from_pbm_rejects_other_sizes() {
+        image_format::rejects_other_sizes::<Pbm>();
+    }
+
+    #[test]
+    fn from_pbm_rejects_truncated_raster() {
+        image_format::rejects_truncated::<Pbm>();
+        assert!(matches!(Fb::from_pbm(b"P5\n296 128\n"), Err(InvalidImage)));
+    }
+
+    #[test]
+    fn decode_rejects_overflowing_size() {
+        assert_eq!(decode(b"P4\n18446744073709551615 16\n\0"), None);
+        assert_eq!(decode(b"P4\n16 18446744073709551615\n\0"), None);
+        assert_eq!(decode(b"P4\n99999999999999999999999 1\n\0"), None);
+    }
+}
@@ -1,5 +1,5 @@
 #![allow(non_snake_case)]
-use crate::Uc8151;
+use crate::BadgerUc8151;
 pub use embassy_rp::peripherals::*;
 use embassy_rp::{
     config::Config,
@@ -34,8 +34,6 @@ pub struct Peripherals {
     pub SPI1: SPI1,
     pub I2C0: I2C0,
     pub I2C1: I2C1,
-    pub DMA_CH0: DMA_CH0,
-    pub DMA_CH1: DMA_CH1,
     pub DMA_CH2: DMA_CH2,
     pub DMA_CH3: DMA_CH3,
     pub DMA_CH4: DMA_CH4,
@@ -71,14 +69,15 @@ pub struct Peripherals {
     pub BUTTON_E: Input<'static>,
     pub BUTTON_USR: Input<'static>,
     pub LED: Output<'static>,
-    pub UC8151: Uc8151,
+    pub UC8151: BadgerUc8151,
 }
 
 pub fn init(config: Config) -> Peripherals {
     let p = embassy_rp::init(config);
     let LED = Output::new(p.PIN_25, Level::Low);
-    let UC8151 = Uc8151::new(
-        p.SPI0, p.PIN_17, p.PIN_18, p.PIN_19, p.PIN_16, p.PIN_20, p.PIN_21, p.PIN_26,
+    let UC8151 = BadgerUc8151::badger(
+        p.SPI0, p.PIN_17, p.PIN_18, p.PIN_19, p.PIN_16, p.PIN_20, p.PIN_21, p.PIN_26, p.DMA_CH0,
+        p.DMA_CH1,
     );
     Peripherals {
         PIN_0: p.PIN_0,
@@ -108,8 +107,6 @@ pub fn init(config: Config) -> Peripherals {
         SPI1: p.SPI1,
         I2C0: p.I2C0,
         I2C1: p.I2C1,
-        DMA_CH0: p.DMA_CH0,
-        DMA_CH1: p.DMA_CH1,
         DMA_CH2: p.DMA_CH2,
         DMA_CH3: p.DMA_CH3,
         DMA_CH4: p.DMA_CH4,
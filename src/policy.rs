@@ -0,0 +1,66 @@
+use embassy_time::{Duration, Instant};
+
+/// Decides when a [`Display`](crate::Display) needs a full clean refresh to
+/// remove the ghosting left behind by partial and fast refreshes.
+///
+/// Partial refreshes and full ones with a [`RefreshSpeed`](crate::RefreshSpeed)
+/// other than `Normal` count as fast. The default policy never forces a
+/// clean refresh.
+#[derive(Clone, Debug, Default)]
+pub struct RefreshPolicy {
+    max_fast_updates: Option<u32>,
+    max_age: Option<Duration>,
+    flash: bool,
+    fast_updates: u32,
+    last_clean: Option<Instant>,
+}
+
+impl RefreshPolicy {
+    /// Force a clean refresh after `max_fast_updates` fast refreshes in a row,
+    /// or when the last clean refresh is older than `max_age`.
+    pub fn new(max_fast_updates: Option<u32>, max_age: Option<Duration>) -> Self {
+        Self {
+            max_fast_updates,
+            max_age,
+            ..Self::default()
+        }
+    }
+
+    /// Flash the panel black and white before each forced clean refresh.
+    pub fn with_flash(mut self, flash: bool) -> Self {
+        self.flash = flash;
+        self
+    }
+
+    pub fn flash(&self) -> bool {
+        self.flash
+    }
+
+    /// Fast refreshes since the last clean one.
+    pub fn fast_updates(&self) -> u32 {
+        self.fast_updates
+    }
+
+    /// Whether the next refresh has to be a clean one.
+    pub fn needs_clean(&self, now: Instant) -> bool {
+        let too_many = self
+            .max_fast_updates
+            .is_some_and(|max| self.fast_updates >= max);
+        let too_old = match (self.max_age, self.last_clean) {
+            (Some(max_age), Some(last_clean)) => now - last_clean >= max_age,
+            // nothing known about the panel before the first clean refresh
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        too_many || too_old
+    }
+
+    pub fn record_fast(&mut self) {
+        self.fast_updates = self.fast_updates.saturating_add(1);
+    }
+
+    pub fn record_clean(&mut self, now: Instant) {
+        self.fast_updates = 0;
+        self.last_clean = Some(now);
+    }
+}
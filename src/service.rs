@@ -0,0 +1,271 @@
+use crate::{Display, Error, Resolution};
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    channel::{Channel, Sender},
+};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Point,
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    text::Text,
+    Drawable,
+};
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, spi::SpiDevice};
+use heapless::String;
+
+/// Longest text a single [`DisplayCommand::Text`] can carry.
+pub const TEXT_CAPACITY: usize = 32;
+
+/// Request sent to a [`DisplayService`].
+#[derive(Clone)]
+pub enum DisplayCommand {
+    /// Fill the whole display with a color.
+    Clear(BinaryColor),
+    /// Fill a rectangle, in display coordinates.
+    FillRect(Rectangle, BinaryColor),
+    /// Draw text with its baseline starting at `position`.
+    Text {
+        position: Point,
+        text: String<TEXT_CAPACITY>,
+        font: &'static MonoFont<'static>,
+        color: BinaryColor,
+    },
+    /// Push all changes so far to the panel.
+    Refresh,
+}
+
+/// Display shared by several tasks.
+///
+/// One task owns the [`Display`] and runs [`Self::run`], all others draw
+/// through cloned [`DisplayClient`]s. Embassy tasks can't be generic, so the
+/// owning task is declared by the application:
+///
+/// ```ignore
+/// static SERVICE: DisplayService<CriticalSectionRawMutex, 8> = DisplayService::new();
+///
+/// #[embassy_executor::task]
+/// async fn display_task(mut display: BadgerDisplay) {
+///     SERVICE.run(&mut display).await
+/// }
+/// ```
+pub struct DisplayService<M: RawMutex, const N: usize> {
+    channel: Channel<M, DisplayCommand, N>,
+}
+
+impl<M: RawMutex, const N: usize> Default for DisplayService<M, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: RawMutex, const N: usize> DisplayService<M, N> {
+    pub const fn new() -> Self {
+        Self {
+            channel: Channel::new(),
+        }
+    }
+
+    pub fn client(&self) -> DisplayClient<'_, M, N> {
+        DisplayClient {
+            sender: self.channel.sender(),
+        }
+    }
+
+    /// Execute commands from all clients, forever.
+    ///
+    /// Commands queued behind a refresh are drawn before the panel is
+    /// updated, and any further refreshes among them are merged into that
+    /// one update. A failed update leaves its changes for the next refresh.
+    pub async fn run<SPI, DC, RESET, BUSY, R, const L: usize>(
+        &self,
+        display: &mut Display<SPI, DC, RESET, BUSY, R, L>,
+    ) -> !
+    where
+        SPI: SpiDevice,
+        DC: OutputPin,
+        RESET: OutputPin,
+        BUSY: Wait,
+        R: Resolution,
+    {
+        loop {
+            let mut refresh = false;
+            let mut command = self.channel.receive().await;
+            loop {
+                match command {
+                    DisplayCommand::Refresh => refresh = true,
+                    command => {
+                        // drawing only fails for pixels outside the display,
+                        // which are clipped anyway
+                        let _ = Self::draw(display, command);
+                    }
+                }
+                if !refresh {
+                    break;
+                }
+                match self.channel.try_receive() {
+                    Ok(next) => command = next,
+                    Err(_) => break,
+                }
+            }
+
+            if refresh {
+                if let Err(error) = display.push_to_display().await {
+                    log::warn!("display refresh failed: {:?}", error);
+                }
+            }
+        }
+    }
+
+    fn draw<SPI, DC, RESET, BUSY, R, const L: usize>(
+        display: &mut Display<SPI, DC, RESET, BUSY, R, L>,
+        command: DisplayCommand,
+    ) -> Result<(), Error<SPI::Error>>
+    where
+        SPI: SpiDevice,
+        DC: OutputPin,
+        RESET: OutputPin,
+        BUSY: Wait,
+        R: Resolution,
+    {
+        match command {
+            DisplayCommand::Clear(color) => display.clear(color),
+            DisplayCommand::FillRect(area, color) => display.fill_solid(&area, color),
+            DisplayCommand::Text {
+                position,
+                text,
+                font,
+                color,
+            } => Text::new(&text, position, MonoTextStyle::new(font, color))
+                .draw(display)
+                .map(|_| ()),
+            DisplayCommand::Refresh => Ok(()),
+        }
+    }
+}
+
+/// Handle for drawing on a [`DisplayService`], cheap to copy into other tasks.
+///
+/// Drawing happens in the service task, these methods only wait for room in
+/// the queue.
+pub struct DisplayClient<'a, M: RawMutex, const N: usize> {
+    sender: Sender<'a, M, DisplayCommand, N>,
+}
+
+impl<'a, M: RawMutex, const N: usize> Clone for DisplayClient<'a, M, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, M: RawMutex, const N: usize> Copy for DisplayClient<'a, M, N> {}
+
+impl<'a, M: RawMutex, const N: usize> DisplayClient<'a, M, N> {
+    pub async fn send(&self, command: DisplayCommand) {
+        self.sender.send(command).await
+    }
+
+    pub async fn clear(&self, color: BinaryColor) {
+        self.send(DisplayCommand::Clear(color)).await
+    }
+
+    pub async fn fill_rect(&self, area: Rectangle, color: BinaryColor) {
+        self.send(DisplayCommand::FillRect(area, color)).await
+    }
+
+    /// Draw `text`, cut off after [`TEXT_CAPACITY`] bytes.
+    pub async fn text(
+        &self,
+        position: Point,
+        text: &str,
+        font: &'static MonoFont<'static>,
+        color: BinaryColor,
+    ) {
+        let mut truncated = String::new();
+        for c in text.chars() {
+            if truncated.push(c).is_err() {
+                break;
+            }
+        }
+        self.send(DisplayCommand::Text {
+            position,
+            text: truncated,
+            font,
+            color,
+        })
+        .await
+    }
+
+    /// Ask for the changes so far to be shown on the panel.
+    pub async fn refresh(&self) {
+        self.send(DisplayCommand::Refresh).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emulator, PanelColor};
+    use core::cell::RefCell;
+    use embassy_futures::{block_on, select::select, yield_now};
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use embedded_graphics::geometry::Size;
+
+    #[test]
+    fn queued_refreshes_are_merged() {
+        let emulator = RefCell::new(Emulator::new());
+        block_on(async {
+            let mut display: Display<_, _, _, _> =
+                Display::new(Emulator::uc8151(&emulator)).await.unwrap();
+            display.push_to_display().await.unwrap();
+            let service = DisplayService::<NoopRawMutex, 8>::new();
+            let client = service.client();
+
+            let first = Rectangle::new(Point::new(0, 0), Size::new(8, 8));
+            let second = Rectangle::new(Point::new(100, 40), Size::new(8, 8));
+            select(service.run(&mut display), async {
+                client.fill_rect(first, BinaryColor::Off).await;
+                client.refresh().await;
+                client.refresh().await;
+                client.fill_rect(second, BinaryColor::Off).await;
+                client.refresh().await;
+                // let the service drain the queue
+                yield_now().await;
+            })
+            .await;
+
+            let emulator = emulator.borrow();
+            assert_eq!(emulator.refreshes(), 2);
+            for area in [first, second] {
+                let corner = area.bottom_right().unwrap();
+                assert_eq!(
+                    emulator.pixel(corner.x as usize, corner.y as usize),
+                    Some(PanelColor::Black)
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn draws_wait_for_a_refresh() {
+        let emulator = RefCell::new(Emulator::new());
+        block_on(async {
+            let mut display: Display<_, _, _, _> =
+                Display::new(Emulator::uc8151(&emulator)).await.unwrap();
+            display.push_to_display().await.unwrap();
+            let service = DisplayService::<NoopRawMutex, 8>::new();
+            let client = service.client();
+
+            select(service.run(&mut display), async {
+                client.clear(BinaryColor::Off).await;
+                yield_now().await;
+            })
+            .await;
+
+            assert_eq!(emulator.borrow().refreshes(), 1);
+            assert_eq!(display.framebuffer().get(0, 0), Some(true));
+        });
+    }
+}
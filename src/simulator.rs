@@ -0,0 +1,103 @@
+use std::{io::Error, path::Path};
+
+use crate::{Emulator, Framebuffer, PanelColor, Resolution};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    pixelcolor::BinaryColor,
+    Pixel,
+};
+use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay};
+#[cfg(feature = "simulator-window")]
+use embedded_graphics_simulator::{SimulatorEvent, Window};
+
+/// Build a simulator display from a function telling which pixels are black.
+fn simulator_display(
+    width: usize,
+    height: usize,
+    black: impl Fn(usize, usize) -> bool,
+) -> SimulatorDisplay<BinaryColor> {
+    let mut display = SimulatorDisplay::new(Size::new(width as u32, height as u32));
+    let pixels = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+    // drawing on the simulator can't fail
+    let _ = display.draw_iter(pixels.map(|(x, y)| {
+        let color = if black(x, y) {
+            BinaryColor::Off
+        } else {
+            BinaryColor::On
+        };
+        Pixel(Point::new(x as i32, y as i32), color)
+    }));
+    display
+}
+
+/// Save a simulator display as PNG, scaled up by `scale`.
+fn save_png(
+    display: &SimulatorDisplay<BinaryColor>,
+    path: impl AsRef<Path>,
+    scale: u32,
+) -> std::io::Result<()> {
+    let settings = OutputSettingsBuilder::new().scale(scale).build();
+    display
+        .to_rgb_output_image(&settings)
+        .save_png(path)
+        .map_err(Error::other)
+}
+
+impl<R: Resolution> Framebuffer<R> {
+    /// Copy into a simulator display, in landscape like [`Rotation::Deg0`].
+    ///
+    /// Black pixels are [`BinaryColor::Off`], as when drawing.
+    ///
+    /// [`Rotation::Deg0`]: crate::Rotation::Deg0
+    pub fn to_simulator_display(&self) -> SimulatorDisplay<BinaryColor> {
+        simulator_display(Self::WIDTH, Self::HEIGHT, |x, y| {
+            self.get(x, y).unwrap_or_default()
+        })
+    }
+
+    /// Save as PNG, scaled up by `scale`.
+    pub fn save_png(&self, path: impl AsRef<Path>, scale: u32) -> std::io::Result<()> {
+        save_png(&self.to_simulator_display(), path, scale)
+    }
+}
+
+impl Emulator {
+    /// Copy the emulated panel into a simulator display, red pixels come out
+    /// black.
+    pub fn to_simulator_display(&self) -> SimulatorDisplay<BinaryColor> {
+        simulator_display(self.width(), self.height(), |x, y| {
+            self.pixel(x, y) != Some(PanelColor::White)
+        })
+    }
+
+    /// Save the emulated panel as PNG, scaled up by `scale`.
+    pub fn save_png(&self, path: impl AsRef<Path>, scale: u32) -> std::io::Result<()> {
+        save_png(&self.to_simulator_display(), path, scale)
+    }
+}
+
+/// Desktop window showing a framebuffer or emulated panel. Needs SDL2.
+#[cfg(feature = "simulator-window")]
+pub struct SimulatorWindow {
+    window: Window,
+}
+
+#[cfg(feature = "simulator-window")]
+impl SimulatorWindow {
+    pub fn new(title: &str, scale: u32) -> Self {
+        let settings = OutputSettingsBuilder::new().scale(scale).build();
+        Self {
+            window: Window::new(title, &settings),
+        }
+    }
+
+    /// Show `display`, returns `false` once the window was closed.
+    pub fn show(&mut self, display: &SimulatorDisplay<BinaryColor>) -> bool {
+        self.window.update(display);
+        !self
+            .window
+            .events()
+            .any(|event| matches!(event, SimulatorEvent::Quit))
+    }
+}
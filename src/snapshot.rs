@@ -0,0 +1,98 @@
+use std::{env, format, fs, path::Path, string::String, vec, vec::Vec};
+
+use crate::{pbm, Framebuffer, Resolution};
+
+/// Environment variable which, when set, makes [`assert_snapshot`] write the
+/// rendered framebuffers as the new snapshots instead of comparing.
+pub const BLESS_SNAPSHOTS: &str = "BLESS_SNAPSHOTS";
+
+/// Compare `framebuffer` bit for bit with the PBM snapshot at `path`.
+///
+/// On a mismatch the rendered image is written next to the snapshot with the
+/// extension `new.pbm`, and the differing pixels in black with `diff.pbm`.
+/// Run with `BLESS_SNAPSHOTS=1` to create or update snapshots.
+///
+/// # Panics
+///
+/// If the snapshot is missing, unreadable or differs from `framebuffer`.
+pub fn assert_snapshot<R: Resolution>(framebuffer: &Framebuffer<R>, path: impl AsRef<Path>) {
+    if let Err(message) = check_snapshot(framebuffer, path.as_ref()) {
+        panic!("{}", message);
+    }
+}
+
+fn check_snapshot<R: Resolution>(framebuffer: &Framebuffer<R>, path: &Path) -> Result<(), String> {
+    let width = Framebuffer::<R>::WIDTH;
+    let height = Framebuffer::<R>::HEIGHT;
+    let actual = encode(width, height, |x, y| {
+        framebuffer.get(x, y).unwrap_or_default()
+    });
+
+    if env::var_os(BLESS_SNAPSHOTS).is_some() {
+        return write(path, &actual);
+    }
+
+    let expected = fs::read(path).map_err(|error| {
+        format!(
+            "can't read snapshot {}: {}, run with {}=1 to create it",
+            path.display(),
+            error,
+            BLESS_SNAPSHOTS
+        )
+    })?;
+    let Some((expected_width, expected_height, raster)) = pbm::decode(&expected) else {
+        return Err(format!("{} is not a binary PBM", path.display()));
+    };
+
+    let new = path.with_extension("new.pbm");
+    if (expected_width, expected_height) != (width, height) {
+        write(&new, &actual)?;
+        return Err(format!(
+            "snapshot {} is {}x{}, rendered {}x{} to {}",
+            path.display(),
+            expected_width,
+            expected_height,
+            width,
+            height,
+            new.display()
+        ));
+    }
+
+    let differs =
+        |x, y| framebuffer.get(x, y).unwrap_or_default() != pbm::pixel(raster, width, x, y);
+    let count = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| differs(x, y))
+        .count();
+    if count == 0 {
+        return Ok(());
+    }
+
+    let diff = path.with_extension("diff.pbm");
+    write(&new, &actual)?;
+    write(&diff, &encode(width, height, differs))?;
+    Err(format!(
+        "{} pixels differ from snapshot {}, see {} and {}, run with {}=1 to accept",
+        count,
+        path.display(),
+        new.display(),
+        diff.display(),
+        BLESS_SNAPSHOTS
+    ))
+}
+
+fn encode(width: usize, height: usize, pixel: impl Fn(usize, usize) -> bool) -> Vec<u8> {
+    // room for the header and the raster
+    let mut buffer = vec![0; 32 + width.div_ceil(8) * height];
+    let len = pbm::encode(width, height, &mut buffer, pixel).expect("buffer fits the image");
+    buffer.truncate(len);
+    buffer
+}
+
+fn write(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("can't create {}: {}", parent.display(), error))?;
+    }
+    fs::write(path, data).map_err(|error| format!("can't write {}: {}", path.display(), error))
+}
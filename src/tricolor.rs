@@ -0,0 +1,156 @@
+use crate::{ColorFormat, Error, Framebuffer, OutOfBounds, Res128x296, Resolution, Uc8151};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::PixelColor,
+    Pixel,
+};
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, spi::SpiDevice};
+
+/// Pixel color of a black/white/red panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TriColor {
+    #[default]
+    White,
+    Black,
+    Red,
+}
+
+impl PixelColor for TriColor {
+    type Raw = ();
+}
+
+/// Framebuffer with one plane for black and one for red pixels.
+pub struct TriColorFramebuffer<R: Resolution = Res128x296> {
+    black: Framebuffer<R>,
+    red: Framebuffer<R>,
+}
+
+impl<R: Resolution> Default for TriColorFramebuffer<R> {
+    fn default() -> Self {
+        Self {
+            black: Framebuffer::default(),
+            red: Framebuffer::default(),
+        }
+    }
+}
+
+impl<R: Resolution> TriColorFramebuffer<R> {
+    pub fn write(&mut self, x: usize, y: usize, color: TriColor) -> Result<(), OutOfBounds> {
+        self.black.write(x, y, color == TriColor::Black)?;
+        self.red.write(x, y, color == TriColor::Red)
+    }
+
+    /// Read back a pixel, `None` if it is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<TriColor> {
+        let color = match (self.black.get(x, y)?, self.red.get(x, y)?) {
+            (_, true) => TriColor::Red,
+            (true, false) => TriColor::Black,
+            (false, false) => TriColor::White,
+        };
+        Some(color)
+    }
+
+    /// Set all pixels to `color`.
+    pub fn fill(&mut self, color: TriColor) {
+        self.black.fill(color == TriColor::Black);
+        self.red.fill(color == TriColor::Red);
+    }
+
+    pub fn black(&self) -> &Framebuffer<R> {
+        &self.black
+    }
+
+    pub fn red(&self) -> &Framebuffer<R> {
+        &self.red
+    }
+}
+
+/// Display for black/white/red UC8151 panels.
+pub struct TriColorDisplay<SPI, DC, RESET, BUSY, R: Resolution = Res128x296> {
+    uc8151: Uc8151<SPI, DC, RESET, BUSY, R>,
+    framebuffer: TriColorFramebuffer<R>,
+}
+
+impl<SPI, DC, RESET, BUSY, R> TriColorDisplay<SPI, DC, RESET, BUSY, R>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
+    /// Initialize display from Uc8151, switching it to the three color format.
+    pub async fn new(
+        mut uc8151: Uc8151<SPI, DC, RESET, BUSY, R>,
+    ) -> Result<Self, Error<SPI::Error>> {
+        uc8151.set_format(ColorFormat::BlackWhiteRed);
+        uc8151.init(uc8151.config()).await?;
+        Ok(Self {
+            uc8151,
+            framebuffer: TriColorFramebuffer::default(),
+        })
+    }
+
+    /// Write both color planes to display and refresh.
+    pub async fn push_to_display(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.uc8151
+            .update_bwr(
+                self.framebuffer.black.as_bytes(),
+                self.framebuffer.red.as_bytes(),
+            )
+            .await
+    }
+
+    /// Clear framebuffer - call [Self::push_to_display] to clear display.
+    pub fn clear_buffer(&mut self) {
+        self.framebuffer.fill(TriColor::White);
+    }
+
+    pub fn framebuffer(&self) -> &TriColorFramebuffer<R> {
+        &self.framebuffer
+    }
+
+    pub fn framebuffer_mut(&mut self) -> &mut TriColorFramebuffer<R> {
+        &mut self.framebuffer
+    }
+}
+
+impl<SPI, DC, RESET, BUSY, R> DrawTarget for TriColorDisplay<SPI, DC, RESET, BUSY, R>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
+    type Color = TriColor;
+    type Error = Error<SPI::Error>;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        for Pixel(pos, color) in pixels.into_iter().filter(|Pixel(pos, _)| bb.contains(*pos)) {
+            self.framebuffer.write(pos.x as _, pos.y as _, color)?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.framebuffer.fill(color);
+        Ok(())
+    }
+}
+
+impl<SPI, DC, RESET, BUSY, R: Resolution> OriginDimensions
+    for TriColorDisplay<SPI, DC, RESET, BUSY, R>
+{
+    fn size(&self) -> Size {
+        Size::new(R::WIDTH as u32, R::HEIGHT as u32)
+    }
+}
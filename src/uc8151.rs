@@ -1,21 +1,53 @@
 #![allow(non_snake_case, non_camel_case_types)]
 
+use core::marker::PhantomData;
+
 use bitflags::bitflags;
+#[cfg(feature = "rp2040")]
 use embassy_rp::{
     gpio::{Input, Level, Output, Pull},
     peripherals::*,
-    spi::{self, Blocking, Spi},
+    spi::{self, Spi},
 };
-use embassy_time::Timer;
-
-pub struct Uc8151 {
-    spi: Spi<'static, SPI0, Blocking>,
-    busy: Input<'static>,
-    chip_select: Output<'static>,
-    dc: Output<'static>,
-    reset: Output<'static>,
+use embassy_time::{with_timeout, Duration, Timer};
+use embedded_graphics::primitives::Rectangle;
+use embedded_hal_1::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, spi::SpiDevice};
+
+#[cfg(feature = "rp2040")]
+use crate::BadgerSpi;
+use crate::{Error, Uc8151Config};
+
+/// Driver for the UC8151 e-paper controller.
+///
+/// Generic over the SPI device (which owns the chip select) and the
+/// data/command, reset and busy pins, so the bus can be shared with other
+/// devices.
+pub struct Uc8151<SPI, DC, RESET, BUSY, R = Res128x296> {
+    spi: SPI,
+    busy: BUSY,
+    dc: DC,
+    reset: RESET,
+    speed: RefreshSpeed,
+    format: ColorFormat,
+    border: Border,
+    inverted: bool,
+    /// Settings of the last [`Self::init`], restored on wake.
+    config: Uc8151Config,
+    busy_timeout: Duration,
+    /// Controller is in deep sleep and needs a reset before it responds.
+    sleeping: bool,
+    /// A refresh was started and not yet waited for.
+    refreshing: bool,
+    /// The data line of the panel is connected to MISO.
+    readable: bool,
+    resolution: PhantomData<R>,
 }
 
+/// UC8151 as wired up on the Badger 2040.
+#[cfg(feature = "rp2040")]
+pub type BadgerUc8151 = Uc8151<BadgerSpi, Output<'static>, Output<'static>, Input<'static>>;
+
 pub enum Register {
     PSR = 0x00,
     PWR = 0x01,
@@ -60,6 +92,7 @@ pub enum Register {
 }
 
 bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct PsrFlags: u8 {
         const RES_96x230   = 0b00000000;
@@ -86,6 +119,7 @@ bitflags! {
         const RESET_NONE   = 0b00000001;
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct PwrFlags1: u8 {
         const VDS_EXTERNAL = 0b00000000;
@@ -95,6 +129,7 @@ bitflags! {
         const VDG_INTERNAL = 0b00000001;
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct PwrFlags2: u8 {
         const VCOM_VD      = 0b00000000;
@@ -106,6 +141,7 @@ bitflags! {
         const VGHL_13V     = 0b00000011;
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct BoosterFlags: u8 {
         const START_10MS = 0b00000000;
@@ -132,6 +168,7 @@ bitflags! {
         const OFF_6_58US = 0b00000111;
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct PfsFlags: u8 {
         const FRAMES_1   = 0b00000000;
@@ -140,6 +177,7 @@ bitflags! {
         const FRAMES_4   = 0b00110000;
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct TseFlags: u8 {
         const TEMP_INTERNAL = 0b00000000;
@@ -164,6 +202,100 @@ bitflags! {
         const OFFSET_MIN_1  = 0b00001111;
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(transparent)]
+    pub struct CdiFlags: u8 {
+        const BORDER_FLOATING   = 0b00000000;
+        const BORDER_WHITE      = 0b01000000;
+        const BORDER_BLACK      = 0b10000000;
+
+        const POLARITY_NORMAL   = 0b00000000;
+        const POLARITY_INVERTED = 0b00010000;
+
+        // vcom and data interval, in hsync periods
+        const INTERVAL_17       = 0b00000000;
+        const INTERVAL_16       = 0b00000001;
+        const INTERVAL_15       = 0b00000010;
+        const INTERVAL_14       = 0b00000011;
+        const INTERVAL_13       = 0b00000100;
+        const INTERVAL_12       = 0b00000101;
+        const INTERVAL_11       = 0b00000110;
+        const INTERVAL_10       = 0b00000111;
+        const INTERVAL_9        = 0b00001000;
+        const INTERVAL_8        = 0b00001001;
+        const INTERVAL_7        = 0b00001010;
+        const INTERVAL_6        = 0b00001011;
+        const INTERVAL_5        = 0b00001100;
+        const INTERVAL_4        = 0b00001101;
+        const INTERVAL_3        = 0b00001110;
+        const INTERVAL_2        = 0b00001111;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(transparent)]
+    pub struct TconFlags: u8 {
+        // non-overlap periods, in units of 660ns
+        const S2G_4      = 0b00000000;
+        const S2G_8      = 0b00010000;
+        const S2G_12     = 0b00100000;
+        const S2G_16     = 0b00110000;
+        const S2G_20     = 0b01000000;
+        const S2G_24     = 0b01010000;
+        const S2G_28     = 0b01100000;
+        const S2G_32     = 0b01110000;
+        const S2G_36     = 0b10000000;
+        const S2G_40     = 0b10010000;
+        const S2G_44     = 0b10100000;
+        const S2G_48     = 0b10110000;
+        const S2G_52     = 0b11000000;
+        const S2G_56     = 0b11010000;
+        const S2G_60     = 0b11100000;
+        const S2G_64     = 0b11110000;
+
+        const G2S_4      = 0b00000000;
+        const G2S_8      = 0b00000001;
+        const G2S_12     = 0b00000010;
+        const G2S_16     = 0b00000011;
+        const G2S_20     = 0b00000100;
+        const G2S_24     = 0b00000101;
+        const G2S_28     = 0b00000110;
+        const G2S_32     = 0b00000111;
+        const G2S_36     = 0b00001000;
+        const G2S_40     = 0b00001001;
+        const G2S_44     = 0b00001010;
+        const G2S_48     = 0b00001011;
+        const G2S_52     = 0b00001100;
+        const G2S_56     = 0b00001101;
+        const G2S_60     = 0b00001110;
+        const G2S_64     = 0b00001111;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(transparent)]
+    pub struct FlgFlags: u8 {
+        const BUSY_N     = 0b00000001;
+        const POF        = 0b00000010;
+        const PON        = 0b00000100;
+        const DATA       = 0b00001000;
+        const I2C_BUSY_N = 0b00010000;
+        const I2C_ERR    = 0b00100000;
+        const PTL        = 0b01000000;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(transparent)]
+    pub struct AmvFlags: u8 {
+        const TIME_3S    = 0b00000000;
+        const TIME_5S    = 0b00010000;
+        const TIME_8S    = 0b00100000;
+        const TIME_10S   = 0b00110000;
+
+        const XON        = 0b00001000;
+        const SOURCE     = 0b00000100;
+        const ENABLE     = 0b00000001;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(transparent)]
     pub struct PllFlags: u8 {
         // other frequency options exist but there doesn't seem to be much
@@ -178,126 +310,978 @@ bitflags! {
     }
 }
 
-impl Uc8151 {
-    pub fn new(
+/// Panel resolution supported by the UC8151.
+///
+/// The panel is addressed in portrait orientation, so the framebuffer width is
+/// the number of gate lines and its height the number of source lines.
+pub trait Resolution {
+    /// Number of gate lines.
+    const WIDTH: usize;
+    /// Number of source lines, always a multiple of 8.
+    const HEIGHT: usize;
+    /// Resolution bits of the panel setting register.
+    const PSR: PsrFlags;
+    /// Storage for one 1bpp frame.
+    type Buffer: AsRef<[u8]> + AsMut<[u8]>;
+    /// Frame with all pixels cleared.
+    const EMPTY: Self::Buffer;
+}
+
+macro_rules! resolution {
+    ($name:ident, $height:literal x $width:literal, $psr:ident) => {
+        #[doc = concat!("Panel with ", $height, "x", $width, " pixels.")]
+        pub struct $name;
+
+        impl Resolution for $name {
+            const WIDTH: usize = $width;
+            const HEIGHT: usize = $height;
+            const PSR: PsrFlags = PsrFlags::$psr;
+            type Buffer = [u8; $width * $height / 8];
+            const EMPTY: Self::Buffer = [0; $width * $height / 8];
+        }
+    };
+}
+
+resolution!(Res96x230, 96 x 230, RES_96x230);
+resolution!(Res96x252, 96 x 252, RES_96x252);
+resolution!(Res128x296, 128 x 296, RES_128x296);
+resolution!(Res160x296, 160 x 296, RES_160x296);
+
+/// Waveform used to refresh the panel, trading image quality for speed.
+///
+/// These are uploaded into the LUT registers and mirror the tables of the
+/// Pimoroni C++ driver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RefreshSpeed {
+    /// Slow but clean refresh, takes about 4.5 seconds.
+    #[default]
+    Normal,
+    /// Refresh in about 2 seconds.
+    Medium,
+    /// Refresh in about 800 milliseconds, with some ghosting.
+    Fast,
+    /// Refresh in about 250 milliseconds, with noticeable ghosting.
+    Turbo,
+}
+
+impl RefreshSpeed {
+    /// Frame counts and repeat count of the three waveform phases.
+    fn timing(self) -> [[u8; 5]; 3] {
+        match self {
+            RefreshSpeed::Normal => [
+                [0x64, 0x64, 0x37, 0x00, 0x01],
+                [0x8c, 0x8c, 0x00, 0x00, 0x04],
+                [0x64, 0x64, 0x37, 0x00, 0x01],
+            ],
+            RefreshSpeed::Medium => [
+                [0x16, 0x16, 0x0d, 0x00, 0x01],
+                [0x23, 0x23, 0x00, 0x00, 0x02],
+                [0x16, 0x16, 0x0d, 0x00, 0x01],
+            ],
+            RefreshSpeed::Fast => [
+                [0x04, 0x04, 0x07, 0x00, 0x01],
+                [0x0c, 0x0c, 0x00, 0x00, 0x02],
+                [0x04, 0x04, 0x07, 0x00, 0x02],
+            ],
+            RefreshSpeed::Turbo => [
+                [0x01, 0x01, 0x02, 0x00, 0x01],
+                [0x02, 0x02, 0x00, 0x00, 0x02],
+                [0x02, 0x02, 0x03, 0x00, 0x02],
+            ],
+        }
+    }
+
+    fn pll(self) -> PllFlags {
+        match self {
+            RefreshSpeed::Normal | RefreshSpeed::Medium => PllFlags::HZ_100,
+            RefreshSpeed::Fast | RefreshSpeed::Turbo => PllFlags::HZ_200,
+        }
+    }
+}
+
+/// Timing of the single phase driving pixels towards black in a grayscale
+/// refresh: each of the three sub-phases darkens one more gray level.
+///
+/// Tuned by eye on a Badger 2040, other panels may need different values.
+const GRAY_TIMING: [[u8; 5]; 3] = [[0x06, 0x06, 0x12, 0x00, 0x01], [0; 5], [0; 5]];
+
+/// Colors the panel can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorFormat {
+    /// Black and white, refreshed with the waveform of the [`RefreshSpeed`].
+    #[default]
+    BlackWhite,
+    /// Black, white and red, refreshed with the waveform from the panel OTP.
+    BlackWhiteRed,
+}
+
+/// Color driven onto the border around the active area of the panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Border {
+    #[default]
+    White,
+    Black,
+    /// Not driven, keeps whatever it showed before.
+    Floating,
+}
+
+/// Revision read from the controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Revision {
+    /// Revision of the waveform stored in the panel OTP.
+    pub lut: [u8; 3],
+    pub chip: u8,
+}
+
+/// Temperature measured by the controller, in steps of 1/8 °C.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Temperature(pub i16);
+
+impl Temperature {
+    /// Whole degrees Celsius, rounded towards minus infinity.
+    pub fn celsius(self) -> i16 {
+        self.0 >> 3
+    }
+}
+
+/// Contents of the whole panel for a refresh.
+enum Frame<'a> {
+    /// Every byte set to the value.
+    Fill(u8),
+    Image(&'a [u8]),
+    /// Image with all pixels flipped.
+    Inverted(&'a [u8]),
+}
+
+/// Build a lookup table from the voltage levels and timing of each phase.
+///
+/// The VCOM table is 44 bytes long, the others only use the first 42.
+fn lut(levels: [u8; 3], timing: [[u8; 5]; 3]) -> [u8; 44] {
+    let mut lut = [0; 44];
+    for (phase, (level, timing)) in levels.iter().zip(timing.iter()).enumerate() {
+        lut[phase * 6] = *level;
+        lut[phase * 6 + 1..phase * 6 + 6].copy_from_slice(timing);
+    }
+    lut
+}
+
+#[cfg(feature = "rp2040")]
+impl BadgerUc8151 {
+    /// Set up the display from the pins it is wired to on the Badger 2040.
+    ///
+    /// The data line of the panel is only connected to MOSI, register reads
+    /// such as [`Uc8151::status`] use it in 3-wire mode, see [`BadgerSpi`].
+    pub fn badger(
         spi: SPI0,
         cs: PIN_17,
         clk: PIN_18,
-        miso: PIN_19,
-        mosi: PIN_16,
+        mosi: PIN_19,
+        miso: PIN_16,
         dc: PIN_20,
         reset: PIN_21,
         busy: PIN_26,
+        tx_dma: DMA_CH0,
+        rx_dma: DMA_CH1,
     ) -> Self {
         let mut config = spi::Config::default();
         config.frequency = 12_000_000;
+        let spi = Spi::new(spi, clk, mosi, miso, tx_dma, rx_dma, config);
+        let mut uc8151 = Self::new(
+            BadgerSpi::new(spi, Output::new(cs, Level::High)),
+            Output::new(dc, Level::Low),
+            Output::new(reset, Level::High),
+            Input::new(busy, Pull::Up),
+        );
+        uc8151.set_readable(true);
+        uc8151
+    }
+}
+
+impl<SPI, DC, RESET, BUSY, R> Uc8151<SPI, DC, RESET, BUSY, R>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RESET: OutputPin,
+    BUSY: Wait,
+    R: Resolution,
+{
+    pub fn new(spi: SPI, dc: DC, reset: RESET, busy: BUSY) -> Self {
         Self {
-            spi: Spi::new_blocking(spi, clk, miso, mosi, config),
-            busy: Input::new(busy, Pull::Up),
-            chip_select: Output::new(cs, Level::High),
-            dc: Output::new(dc, Level::Low),
-            reset: Output::new(reset, Level::High),
+            spi,
+            busy,
+            dc,
+            reset,
+            speed: RefreshSpeed::default(),
+            format: ColorFormat::default(),
+            border: Border::default(),
+            inverted: false,
+            config: Uc8151Config::default(),
+            busy_timeout: Duration::from_secs(10),
+            sleeping: false,
+            refreshing: false,
+            readable: false,
+            resolution: PhantomData,
         }
     }
 
-    pub fn command(&mut self, register: Register, data: &[u8]) {
-        self.chip_select.set_low();
-        self.dc.set_low();
-        // no errors right now in spi lib
-        let _ = self.spi.blocking_write(&[register as u8]);
-        self.dc.set_high();
-        let _ = self.spi.blocking_write(data);
-        self.chip_select.set_high();
+    /// Whether registers can be read, see [`Self::set_readable`].
+    pub fn is_readable(&self) -> bool {
+        self.readable
+    }
+
+    /// Tell whether the panel's answers reach the SPI device.
+    ///
+    /// The panel answers reads on its bidirectional data line, which needs to
+    /// be connected to MISO or read by the SPI device in 3-wire mode. Until
+    /// this is enabled reads fail with [`Error::ReadUnsupported`], instead of
+    /// returning whatever MISO floats at.
+    pub fn set_readable(&mut self, readable: bool) {
+        self.readable = readable;
+    }
+
+    /// How long to wait for the panel before giving up with [`Error::BusyTimeout`].
+    pub fn set_busy_timeout(&mut self, timeout: Duration) {
+        self.busy_timeout = timeout;
     }
 
-    pub async fn busy_wait(&mut self) {
-        self.busy.wait_for_high().await;
+    pub async fn command(
+        &mut self,
+        register: Register,
+        data: &[u8],
+    ) -> Result<(), Error<SPI::Error>> {
+        self.dc.set_low().map_err(|_| Error::Pin)?;
+        self.spi
+            .write(&[register as u8])
+            .await
+            .map_err(Error::Spi)?;
+        self.data(data).await
     }
 
-    pub async fn reset(&mut self) {
-        self.reset.set_low();
+    /// Send more data for the previously issued command.
+    pub async fn data(&mut self, data: &[u8]) -> Result<(), Error<SPI::Error>> {
+        self.dc.set_high().map_err(|_| Error::Pin)?;
+        self.spi.write(data).await.map_err(Error::Spi)
+    }
+
+    pub async fn busy_wait(&mut self) -> Result<(), Error<SPI::Error>> {
+        with_timeout(self.busy_timeout, self.busy.wait_for_high())
+            .await
+            .map_err(|_| Error::BusyTimeout)?
+            .map_err(|_| Error::Pin)
+    }
+
+    pub async fn reset(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.reset.set_low().map_err(|_| Error::Pin)?;
         Timer::after_millis(10).await;
-        self.reset.set_high();
+        self.reset.set_high().map_err(|_| Error::Pin)?;
         Timer::after_millis(10).await;
-        self.busy_wait().await;
+        self.busy_wait().await
     }
 
-    pub async fn init(&mut self) {
-        self.reset().await;
+    /// Reset and configure the controller, keeping `config` for later wakes.
+    pub async fn init(&mut self, config: Uc8151Config) -> Result<(), Error<SPI::Error>> {
+        self.config = config;
+        self.reset().await?;
+        self.sleeping = false;
+        self.refreshing = false;
 
+        let format = match self.format {
+            ColorFormat::BlackWhite => PsrFlags::LUT_REG | PsrFlags::FORMAT_BW,
+            ColorFormat::BlackWhiteRed => PsrFlags::LUT_OTP | PsrFlags::FORMAT_BWR,
+        };
         self.command(
             Register::PSR,
-            &[(PsrFlags::RES_128x296
-                | PsrFlags::LUT_OTP
-                | PsrFlags::FORMAT_BW
+            &[(R::PSR
+                | format
                 | PsrFlags::SHIFT_RIGHT
                 | PsrFlags::BOOSTER_ON
                 | PsrFlags::RESET_NONE)
                 .bits()],
-        );
+        )
+        .await?;
+
+        // resolution setting, overrides the one from the panel setting
+        self.command(
+            Register::TRES,
+            &[R::HEIGHT as u8, (R::WIDTH >> 8) as u8, R::WIDTH as u8],
+        )
+        .await?;
 
         self.command(
             Register::PWR,
             &[
-                (PwrFlags1::VDS_INTERNAL | PwrFlags1::VDG_INTERNAL).bits(),
-                (PwrFlags2::VCOM_VD | PwrFlags2::VGHL_16V).bits(),
-                0b101011,
-                0b101011,
-                0b101011,
+                config.power_source.bits(),
+                config.gate_voltage.bits(),
+                config.source_high,
+                config.source_low,
+                config.source_red,
             ],
-        );
+        )
+        .await?;
 
-        self.command(Register::PON, &[]);
+        self.command(Register::PON, &[]).await?;
 
-        self.busy_wait().await;
+        self.busy_wait().await?;
 
         self.command(
             Register::BTST,
-            &[
-                (BoosterFlags::START_10MS | BoosterFlags::STRENGTH_3 | BoosterFlags::OFF_6_58US)
-                    .bits(),
-                (BoosterFlags::START_10MS | BoosterFlags::STRENGTH_3 | BoosterFlags::OFF_6_58US)
-                    .bits(),
-                (BoosterFlags::START_10MS | BoosterFlags::STRENGTH_3 | BoosterFlags::OFF_6_58US)
-                    .bits(),
-            ],
-        );
+            &config.booster.map(|booster| booster.bits()),
+        )
+        .await?;
 
-        self.command(Register::PFS, &[PfsFlags::FRAMES_1.bits()]);
+        self.command(Register::PFS, &[PfsFlags::FRAMES_1.bits()])
+            .await?;
         self.command(
             Register::TSE,
             &[(TseFlags::TEMP_INTERNAL | TseFlags::OFFSET_0).bits()],
-        );
+        )
+        .await?;
 
-        // tcon setting
-        self.command(Register::TCON, &[0x22]);
+        self.command(
+            Register::TCON,
+            &[(TconFlags::S2G_12 | TconFlags::G2S_12).bits()],
+        )
+        .await?;
+
+        self.command(Register::CDI, &[self.cdi().bits()]).await?;
 
-        // vcom and data interval
-        self.command(Register::CDI, &[0b01001100]);
+        if let Some(vcom) = config.vcom {
+            self.command(Register::VDCS, &[vcom]).await?;
+        }
 
-        self.command(Register::PLL, &[PllFlags::HZ_100.bits()]);
+        if self.format == ColorFormat::BlackWhite {
+            self.upload_luts().await?;
+        } else {
+            // the OTP waveforms would run at the reset frame rate otherwise
+            self.command(Register::PLL, &[self.frame_rate().bits()])
+                .await?;
+        }
 
         // turn off
-        self.command(Register::POF, &[]);
-        self.busy_wait().await;
+        self.command(Register::POF, &[]).await?;
+        self.busy_wait().await
+    }
+
+    /// Currently selected refresh speed.
+    pub fn speed(&self) -> RefreshSpeed {
+        self.speed
     }
 
-    pub async fn update(&mut self, framebuffer: &[u8]) {
+    /// Change the refresh speed, takes effect with the next update.
+    pub async fn set_speed(&mut self, speed: RefreshSpeed) -> Result<(), Error<SPI::Error>> {
+        self.speed = speed;
+        // don't swap the waveform under a running refresh
+        self.finish_refresh().await?;
+        if self.sleeping || self.format != ColorFormat::BlackWhite {
+            // uploaded by init on wake, unused for other formats
+            return Ok(());
+        }
+        self.upload_luts().await
+    }
+
+    pub fn format(&self) -> ColorFormat {
+        self.format
+    }
+
+    /// Select the color format, takes effect with the next [`Self::init`].
+    pub fn set_format(&mut self, format: ColorFormat) {
+        self.format = format;
+    }
+
+    /// Settings of the last [`Self::init`].
+    pub fn config(&self) -> Uc8151Config {
+        self.config
+    }
+
+    pub fn border(&self) -> Border {
+        self.border
+    }
+
+    /// Change the border color, shown from the next full update on.
+    pub async fn set_border(&mut self, border: Border) -> Result<(), Error<SPI::Error>> {
+        self.border = border;
+        self.upload_cdi().await
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Swap black and white for all following updates, without touching the
+    /// framebuffer.
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), Error<SPI::Error>> {
+        self.inverted = inverted;
+        self.upload_cdi().await
+    }
+
+    /// Border and polarity settings for the CDI register.
+    fn cdi(&self) -> CdiFlags {
+        let border = match self.border {
+            Border::White => CdiFlags::BORDER_WHITE,
+            Border::Black => CdiFlags::BORDER_BLACK,
+            Border::Floating => CdiFlags::BORDER_FLOATING,
+        };
+        let polarity = if self.inverted {
+            CdiFlags::POLARITY_INVERTED
+        } else {
+            CdiFlags::POLARITY_NORMAL
+        };
+        border | polarity | CdiFlags::INTERVAL_5
+    }
+
+    async fn upload_cdi(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.finish_refresh().await?;
+        if self.sleeping {
+            // uploaded by init on wake
+            return Ok(());
+        }
+        self.command(Register::CDI, &[self.cdi().bits()]).await
+    }
+
+    /// Whether the controller is in deep sleep.
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Power off the panel and put the controller into deep sleep.
+    ///
+    /// The panel keeps showing its image. The controller only responds again
+    /// after [`Self::wake`], which updates do automatically.
+    pub async fn sleep(&mut self) -> Result<(), Error<SPI::Error>> {
+        if self.sleeping {
+            return Ok(());
+        }
+        self.finish_refresh().await?;
+
+        self.command(Register::POF, &[]).await?;
+        self.busy_wait().await?;
+
+        // check code, required for the controller to accept the command
+        self.command(Register::DSLP, &[0xa5]).await?;
+        self.sleeping = true;
+        Ok(())
+    }
+
+    /// Bring the controller out of deep sleep, restoring the configuration.
+    pub async fn wake(&mut self) -> Result<(), Error<SPI::Error>> {
+        if !self.sleeping {
+            return Ok(());
+        }
+
+        self.init(self.config).await
+    }
+
+    /// Send `register` and read the answer of the controller into `buffer`.
+    ///
+    /// The panel answers on its bidirectional data line, so this only works
+    /// when the SPI device can read that line, see [`Self::set_readable`].
+    pub async fn read(
+        &mut self,
+        register: Register,
+        buffer: &mut [u8],
+    ) -> Result<(), Error<SPI::Error>> {
+        self.check_readable()?;
+        // leaves data/command high for reading
+        self.command(register, &[]).await?;
+        self.spi.read(buffer).await.map_err(Error::Spi)
+    }
+
+    fn check_readable(&self) -> Result<(), Error<SPI::Error>> {
+        if !self.readable {
+            return Err(Error::ReadUnsupported);
+        }
+        Ok(())
+    }
+
+    /// Fail with [`Error::WrongFormat`] unless `format` is selected.
+    fn check_format(&self, format: ColorFormat) -> Result<(), Error<SPI::Error>> {
+        if self.format != format {
+            return Err(Error::WrongFormat);
+        }
+        Ok(())
+    }
+
+    /// Waveform and chip revision.
+    pub async fn revision(&mut self) -> Result<Revision, Error<SPI::Error>> {
+        self.check_readable()?;
+        self.wake().await?;
+        let mut buffer = [0; 4];
+        self.read(Register::REV, &mut buffer).await?;
+        Ok(Revision {
+            lut: [buffer[0], buffer[1], buffer[2]],
+            chip: buffer[3],
+        })
+    }
+
+    /// Controller status flags.
+    pub async fn status(&mut self) -> Result<FlgFlags, Error<SPI::Error>> {
+        self.check_readable()?;
+        self.wake().await?;
+        let mut buffer = [0];
+        self.read(Register::FLG, &mut buffer).await?;
+        Ok(FlgFlags::from_bits_retain(buffer[0]))
+    }
+
+    /// Measure the temperature with the sensor selected in the TSE register,
+    /// the internal one by default.
+    ///
+    /// The waveform timing can be picked from this, the panel refreshes
+    /// slower when it is cold.
+    pub async fn temperature(&mut self) -> Result<Temperature, Error<SPI::Error>> {
+        self.check_readable()?;
+        self.wake().await?;
+        self.finish_refresh().await?;
+
+        // the controller starts measuring on the command and answers when done
+        self.command(Register::TSC, &[]).await?;
+        self.busy_wait().await?;
+        let mut buffer = [0; 2];
+        self.spi.read(&mut buffer).await.map_err(Error::Spi)?;
+
+        // 11 bit two's complement, left aligned
+        Ok(Temperature(i16::from_be_bytes(buffer) >> 5))
+    }
+
+    /// Whether the supply voltage dropped below 2.5 V.
+    pub async fn low_power(&mut self) -> Result<bool, Error<SPI::Error>> {
+        self.check_readable()?;
+        self.wake().await?;
+        let mut buffer = [0];
+        self.read(Register::LPD, &mut buffer).await?;
+        // cleared bit means low power
+        Ok(buffer[0] & 0b1 == 0)
+    }
+
+    /// Let the controller measure the optimal VCOM level of the panel.
+    ///
+    /// Takes about 3 seconds. The result uses the scale of
+    /// [`Uc8151Config::with_vcom`].
+    pub async fn measure_vcom(&mut self) -> Result<u8, Error<SPI::Error>> {
+        self.check_readable()?;
+        self.wake().await?;
+        self.finish_refresh().await?;
+
+        self.command(Register::PON, &[]).await?;
+        self.busy_wait().await?;
+
+        self.command(
+            Register::AMV,
+            &[(AmvFlags::TIME_3S | AmvFlags::ENABLE).bits()],
+        )
+        .await?;
+        self.busy_wait().await?;
+
+        let mut buffer = [0];
+        self.read(Register::VV, &mut buffer).await?;
+
+        self.command(Register::POF, &[]).await?;
+        self.busy_wait().await?;
+        Ok(buffer[0] & 0b111111)
+    }
+
+    async fn upload_luts(&mut self) -> Result<(), Error<SPI::Error>> {
+        let timing = self.speed.timing();
+        self.command(Register::LUT_VCOM, &lut([0x00, 0x00, 0x00], timing))
+            .await?;
+        self.command(Register::LUT_WW, &lut([0x54, 0x60, 0xa8], timing)[..42])
+            .await?;
+        self.command(Register::LUT_BW, &lut([0x54, 0x60, 0xa8], timing)[..42])
+            .await?;
+        self.command(Register::LUT_WB, &lut([0xa8, 0x60, 0x54], timing)[..42])
+            .await?;
+        self.command(Register::LUT_BB, &lut([0xa8, 0x60, 0x54], timing)[..42])
+            .await?;
+        self.command(Register::PLL, &[self.frame_rate().bits()])
+            .await
+    }
+
+    /// Configured frame rate, or the one for the format and speed.
+    fn frame_rate(&self) -> PllFlags {
+        self.config.frame_rate.unwrap_or(match self.format {
+            ColorFormat::BlackWhite => self.speed.pll(),
+            ColorFormat::BlackWhiteRed => PllFlags::HZ_100,
+        })
+    }
+
+    /// Waveforms selecting one of four gray levels by the bits of both planes.
+    ///
+    /// The controller picks the LUT by old (DTM1) and new (DTM2) pixel data,
+    /// which here carry the high and low bit of the gray level.
+    async fn upload_gray_luts(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.command(Register::LUT_VCOM, &lut([0x00, 0x00, 0x00], GRAY_TIMING))
+            .await?;
+        // white
+        self.command(
+            Register::LUT_WW,
+            &lut([0x00, 0x00, 0x00], GRAY_TIMING)[..42],
+        )
+        .await?;
+        // light gray
+        self.command(
+            Register::LUT_WB,
+            &lut([0x40, 0x00, 0x00], GRAY_TIMING)[..42],
+        )
+        .await?;
+        // dark gray
+        self.command(
+            Register::LUT_BW,
+            &lut([0x50, 0x00, 0x00], GRAY_TIMING)[..42],
+        )
+        .await?;
+        // black
+        self.command(
+            Register::LUT_BB,
+            &lut([0x54, 0x00, 0x00], GRAY_TIMING)[..42],
+        )
+        .await?;
+        let frame_rate = self.config.frame_rate.unwrap_or(PllFlags::HZ_100);
+        self.command(Register::PLL, &[frame_rate.bits()]).await
+    }
+
+    /// Show four levels of gray in two passes.
+    ///
+    /// The first pass cleans the panel to white with the slow waveform, the
+    /// second darkens each pixel by an amount picked from its bits in `high`
+    /// and `low` (set bits are darker). Fails with [`Error::WrongFormat`]
+    /// unless the format is [`ColorFormat::BlackWhite`].
+    pub async fn update_gray(&mut self, high: &[u8], low: &[u8]) -> Result<(), Error<SPI::Error>> {
+        let len = R::WIDTH * R::HEIGHT / 8;
+        if high.len() != len || low.len() != len {
+            return Err(Error::OutOfBounds);
+        }
+        self.check_format(ColorFormat::BlackWhite)?;
+        if self.refreshing {
+            return Err(Error::RefreshInProgress);
+        }
+        self.wake().await?;
+
+        // faster waveforms don't clean the panel to an even white
+        let speed = self.speed;
+        let result = match self.set_speed(RefreshSpeed::Normal).await {
+            Ok(()) => self.refresh_gray(high, low).await,
+            Err(error) => Err(error),
+        };
+        // restore the waveform for regular updates, also after errors
+        let restored = self.set_speed(speed).await;
+        result.and(restored)?;
+
+        self.command(Register::POF, &[]).await // turn off
+    }
+
+    async fn refresh_gray(&mut self, high: &[u8], low: &[u8]) -> Result<(), Error<SPI::Error>> {
+        // turn on
+        self.command(Register::PON, &[]).await?;
+
+        // disable partial mode
+        self.command(Register::PTOU, &[]).await?;
+
+        // clean to white
+        self.command(Register::DTM2, &[]).await?;
+        self.data_repeat(0x00, high.len()).await?;
+        self.command(Register::DSP, &[]).await?;
+        self.command(Register::DRF, &[]).await?;
+        self.busy_wait().await?;
+
+        // darken to gray levels
+        self.upload_gray_luts().await?;
+        self.command(Register::DTM1, high).await?;
+        self.command(Register::DTM2, low).await?;
+        self.command(Register::DSP, &[]).await?;
+        self.command(Register::DRF, &[]).await?;
+        self.busy_wait().await
+    }
+
+    /// Send the framebuffer and refresh the whole panel.
+    ///
+    /// Requires [`ColorFormat::BlackWhite`], see [`Self::update_bwr`] for the
+    /// other format.
+    pub async fn update(&mut self, framebuffer: &[u8]) -> Result<(), Error<SPI::Error>> {
+        self.start_update(framebuffer).await?;
+        self.finish_refresh().await
+    }
+
+    /// Send the framebuffer and start refreshing the whole panel, without
+    /// waiting for the refresh to finish.
+    ///
+    /// The framebuffer is no longer needed once this returns. Call
+    /// [`Self::finish_refresh`] before anything else is sent to the panel.
+    pub async fn start_update(&mut self, framebuffer: &[u8]) -> Result<(), Error<SPI::Error>> {
+        if framebuffer.len() != R::WIDTH * R::HEIGHT / 8 {
+            return Err(Error::OutOfBounds);
+        }
+        self.check_format(ColorFormat::BlackWhite)?;
+        if self.refreshing {
+            return Err(Error::RefreshInProgress);
+        }
+        self.wake().await?;
+
         // turn on
-        self.command(Register::PON, &[]);
+        self.command(Register::PON, &[]).await?;
 
         // disable partial mode
-        self.command(Register::PTOU, &[]);
+        self.command(Register::PTOU, &[]).await?;
 
         // transmit framebuffer
-        self.command(Register::DTM2, framebuffer);
+        self.command(Register::DTM2, framebuffer).await?;
         // data stop
-        self.command(Register::DSP, &[]);
+        self.command(Register::DSP, &[]).await?;
 
-        self.command(Register::DRF, &[]); // start display refresh
+        self.command(Register::DRF, &[]).await?; // start display refresh
+        self.refreshing = true;
+        Ok(())
+    }
+
+    /// Whether a refresh was started and not yet waited for.
+    pub fn is_refreshing(&self) -> bool {
+        self.refreshing
+    }
 
-        self.busy_wait().await;
+    /// Wait for a refresh started by [`Self::start_update`] or
+    /// [`Self::start_partial_update`] and power off the panel.
+    ///
+    /// Returns immediately if no refresh is running. After a timeout the
+    /// refresh counts as still running, so this can be retried.
+    pub async fn finish_refresh(&mut self) -> Result<(), Error<SPI::Error>> {
+        if !self.refreshing {
+            return Ok(());
+        }
+
+        self.busy_wait().await?;
+        self.refreshing = false;
+
+        self.command(Register::POF, &[]).await // turn off
+    }
+
+    /// Refresh the whole panel with the slow, clean waveform, whatever the
+    /// selected speed.
+    ///
+    /// Requires [`ColorFormat::BlackWhite`], like the other clean refreshes.
+    pub async fn clean_update(&mut self, framebuffer: &[u8]) -> Result<(), Error<SPI::Error>> {
+        self.refresh_frames(&[Frame::Image(framebuffer)], 1).await
+    }
+
+    /// Refresh the panel all black, then all white, to shake off ghosting.
+    ///
+    /// Requires [`ColorFormat::BlackWhite`], like the other clean refreshes.
+    pub async fn flash(&mut self) -> Result<(), Error<SPI::Error>> {
+        self.refresh_frames(&[Frame::Fill(0xff), Frame::Fill(0x00)], 1)
+            .await
+    }
+
+    /// Remove stubborn ghosting by driving every pixel through both colors.
+    ///
+    /// Each pass refreshes with the inverted framebuffer, then with the
+    /// framebuffer itself, using the slow waveform. Takes about 9 seconds per
+    /// pass and ends showing `framebuffer`. Requires [`ColorFormat::BlackWhite`].
+    pub async fn deep_clean(
+        &mut self,
+        framebuffer: &[u8],
+        passes: usize,
+    ) -> Result<(), Error<SPI::Error>> {
+        self.refresh_frames(
+            &[Frame::Inverted(framebuffer), Frame::Image(framebuffer)],
+            passes,
+        )
+        .await
+    }
+
+    /// Show `frames` in order, `repeat` times, with the normal waveform.
+    ///
+    /// Fails with [`Error::WrongFormat`] unless the format is
+    /// [`ColorFormat::BlackWhite`], the other formats use the waveform from
+    /// the panel OTP.
+    async fn refresh_frames(
+        &mut self,
+        frames: &[Frame<'_>],
+        repeat: usize,
+    ) -> Result<(), Error<SPI::Error>> {
+        let len = R::WIDTH * R::HEIGHT / 8;
+        for frame in frames {
+            if let Frame::Image(data) | Frame::Inverted(data) = frame {
+                if data.len() != len {
+                    return Err(Error::OutOfBounds);
+                }
+            }
+        }
+        self.check_format(ColorFormat::BlackWhite)?;
+        if self.refreshing {
+            return Err(Error::RefreshInProgress);
+        }
+
+        let speed = self.speed;
+        let result = match self.set_speed(RefreshSpeed::Normal).await {
+            Ok(()) => self.refresh_frames_inner(frames, repeat).await,
+            Err(error) => Err(error),
+        };
+        // restore the selected waveform, also after errors
+        let restored = self.set_speed(speed).await;
+        result.and(restored)
+    }
+
+    async fn refresh_frames_inner(
+        &mut self,
+        frames: &[Frame<'_>],
+        repeat: usize,
+    ) -> Result<(), Error<SPI::Error>> {
+        self.wake().await?;
+
+        // turn on
+        self.command(Register::PON, &[]).await?;
+
+        // disable partial mode
+        self.command(Register::PTOU, &[]).await?;
+
+        for _ in 0..repeat {
+            for frame in frames {
+                self.command(Register::DTM2, &[]).await?;
+                match *frame {
+                    Frame::Fill(value) => self.data_repeat(value, R::WIDTH * R::HEIGHT / 8).await?,
+                    Frame::Image(data) => self.data(data).await?,
+                    Frame::Inverted(data) => self.data_inverted(data).await?,
+                }
+                self.command(Register::DSP, &[]).await?;
+                self.command(Register::DRF, &[]).await?;
+                self.busy_wait().await?;
+            }
+        }
+
+        self.command(Register::POF, &[]).await // turn off
+    }
+
+    /// Send both color planes of a black/white/red panel and refresh it.
+    ///
+    /// Bits set in `black` or `red` are pixels of that color, everything else
+    /// is white. Requires [`ColorFormat::BlackWhiteRed`].
+    pub async fn update_bwr(&mut self, black: &[u8], red: &[u8]) -> Result<(), Error<SPI::Error>> {
+        let len = R::WIDTH * R::HEIGHT / 8;
+        if black.len() != len || red.len() != len {
+            return Err(Error::OutOfBounds);
+        }
+        self.check_format(ColorFormat::BlackWhiteRed)?;
+        if self.refreshing {
+            return Err(Error::RefreshInProgress);
+        }
+        self.wake().await?;
+
+        // turn on
+        self.command(Register::PON, &[]).await?;
+
+        // disable partial mode
+        self.command(Register::PTOU, &[]).await?;
+
+        // the controller expects cleared bits for black and red pixels
+        self.command(Register::DTM1, &[]).await?;
+        self.data_inverted(black).await?;
+        self.command(Register::DTM2, &[]).await?;
+        self.data_inverted(red).await?;
+        // data stop
+        self.command(Register::DSP, &[]).await?;
+
+        self.command(Register::DRF, &[]).await?; // start display refresh
+
+        self.busy_wait().await?;
+
+        self.command(Register::POF, &[]).await // turn off
+    }
+
+    /// Send data with all bits flipped, in small chunks.
+    async fn data_inverted(&mut self, data: &[u8]) -> Result<(), Error<SPI::Error>> {
+        let mut buffer = [0; 64];
+        for chunk in data.chunks(buffer.len()) {
+            for (inverted, byte) in buffer.iter_mut().zip(chunk) {
+                *inverted = !*byte;
+            }
+            self.data(&buffer[..chunk.len()]).await?;
+        }
+        Ok(())
+    }
+
+    /// Send `len` copies of `byte` as data, in small chunks.
+    async fn data_repeat(&mut self, byte: u8, len: usize) -> Result<(), Error<SPI::Error>> {
+        let buffer = [byte; 64];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(buffer.len());
+            self.data(&buffer[..chunk]).await?;
+            remaining -= chunk;
+        }
+        Ok(())
+    }
+
+    /// Refresh only the given region of the panel.
+    ///
+    /// The region is in framebuffer coordinates. Its vertical extent must be
+    /// aligned to 8 pixels, because the controller addresses columns in bytes.
+    pub async fn partial_update(
+        &mut self,
+        framebuffer: &[u8],
+        region: Rectangle,
+    ) -> Result<(), Error<SPI::Error>> {
+        self.start_partial_update(framebuffer, region).await?;
+        self.finish_refresh().await
+    }
+
+    /// Like [`Self::start_update`], but only for `region`.
+    pub async fn start_partial_update(
+        &mut self,
+        framebuffer: &[u8],
+        region: Rectangle,
+    ) -> Result<(), Error<SPI::Error>> {
+        let Some(bottom_right) = region.bottom_right() else {
+            return Ok(());
+        };
+        if framebuffer.len() != R::WIDTH * R::HEIGHT / 8
+            || region.top_left.x < 0
+            || region.top_left.y < 0
+            || bottom_right.x as usize >= R::WIDTH
+            || bottom_right.y as usize >= R::HEIGHT
+            || region.top_left.y % 8 != 0
+            || region.size.height % 8 != 0
+        {
+            return Err(Error::OutOfBounds);
+        }
+        self.check_format(ColorFormat::BlackWhite)?;
+        if self.refreshing {
+            return Err(Error::RefreshInProgress);
+        }
+        self.wake().await?;
+
+        let x = region.top_left.x as usize;
+        let y = region.top_left.y as usize;
+        let width = region.size.width as usize;
+        let height = region.size.height as usize;
+        let stride = R::HEIGHT / 8;
+
+        let x_end = x + width - 1;
+        let y_end = y + height - 1;
+        let window = [
+            y as u8,
+            y_end as u8,
+            (x >> 8) as u8,
+            x as u8,
+            (x_end >> 8) as u8,
+            x_end as u8,
+            0b00000001, // PT_SCAN
+        ];
+
+        // turn on
+        self.command(Register::PON, &[]).await?;
+
+        // enable partial mode
+        self.command(Register::PTIN, &[]).await?;
+        self.command(Register::PTL, &window).await?;
+
+        // transmit the window, one column at a time
+        self.command(Register::DTM2, &[]).await?;
+        for column in x..=x_end {
+            let start = y / 8 + column * stride;
+            self.data(&framebuffer[start..start + height / 8]).await?;
+        }
+        // data stop
+        self.command(Register::DSP, &[]).await?;
 
-        self.command(Register::POF, &[]); // turn off
+        self.command(Register::DRF, &[]).await?; // start display refresh
+        self.refreshing = true;
+        Ok(())
     }
 }
@@ -0,0 +1,311 @@
+//! Drives the display stack against the emulated UC8151 and checks what ends
+//! up on the panel.
+
+use core::cell::RefCell;
+
+use embassy_badger2040::{
+    ColorFormat, Display, Emulator, Error, FlgFlags, Framebuffer, PanelColor, PsrFlags,
+    RefreshSpeed, Register, Res128x296, Res160x296, Res96x230, Res96x252, Resolution, Revision,
+    Temperature, TriColor, TriColorDisplay, Uc8151Config,
+};
+use embassy_futures::block_on;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// Assert that the panel shows `expected(x, y)` for every pixel.
+fn assert_panel(emulator: &RefCell<Emulator>, expected: impl Fn(usize, usize) -> PanelColor) {
+    let emulator = emulator.borrow();
+    for x in 0..emulator.width() {
+        for y in 0..emulator.height() {
+            assert_eq!(
+                emulator.pixel(x, y),
+                Some(expected(x, y)),
+                "at {}, {}",
+                x,
+                y
+            );
+        }
+    }
+}
+
+fn color(black: bool) -> PanelColor {
+    if black {
+        PanelColor::Black
+    } else {
+        PanelColor::White
+    }
+}
+
+/// Assert that the panel shows `framebuffer`.
+fn assert_shows<R: Resolution>(emulator: &RefCell<Emulator>, framebuffer: &Framebuffer<R>) {
+    assert_panel(emulator, |x, y| color(framebuffer.get(x, y).unwrap()));
+}
+
+#[test]
+fn full_push() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let mut display: Display<_, _, _, _> =
+            Display::new(Emulator::uc8151(&emulator)).await.unwrap();
+        *display.framebuffer_mut() = Framebuffer::stripes();
+        display.push_full_to_display().await.unwrap();
+
+        assert_eq!(emulator.borrow().refreshes(), 1);
+        assert_shows(&emulator, display.framebuffer());
+    });
+}
+
+#[test]
+fn partial_window_push() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let mut uc8151 = Emulator::uc8151::<Res128x296>(&emulator);
+        uc8151.init(Uc8151Config::default()).await.unwrap();
+        uc8151
+            .update(Framebuffer::<Res128x296>::default().as_bytes())
+            .await
+            .unwrap();
+
+        let framebuffer = Framebuffer::<Res128x296>::stripes();
+        let window = Rectangle::new(Point::new(40, 16), Size::new(50, 32));
+        uc8151
+            .partial_update(framebuffer.as_bytes(), window)
+            .await
+            .unwrap();
+
+        // only the window shows the new contents
+        assert_eq!(emulator.borrow().refreshes(), 2);
+        assert_panel(&emulator, |x, y| {
+            let inside = window.contains(Point::new(x as i32, y as i32));
+            color(inside && framebuffer.get(x, y).unwrap())
+        });
+    });
+}
+
+#[test]
+fn display_partial_push() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let mut display: Display<_, _, _, _> =
+            Display::new(Emulator::uc8151(&emulator)).await.unwrap();
+        *display.framebuffer_mut() = Framebuffer::stripes();
+        display.push_to_display().await.unwrap();
+
+        // show something else behind the back of the display
+        let mut other = Framebuffer::<Res128x296>::stripes();
+        other.invert();
+        display.uc8151_mut().update(other.as_bytes()).await.unwrap();
+
+        display
+            .draw_iter([Pixel(Point::new(201, 100), BinaryColor::Off)])
+            .unwrap();
+        display.push_to_display().await.unwrap();
+
+        // only the byte-aligned window around the pixel was refreshed
+        assert_eq!(emulator.borrow().refreshes(), 3);
+        let window = Rectangle::new(Point::new(201, 96), Size::new(1, 8));
+        assert_panel(&emulator, |x, y| {
+            if window.contains(Point::new(x as i32, y as i32)) {
+                color(display.framebuffer().get(x, y).unwrap())
+            } else {
+                color(other.get(x, y).unwrap())
+            }
+        });
+        assert_eq!(display.framebuffer().get(201, 100), Some(true));
+    });
+}
+
+async fn check_resolution<R: Resolution>() {
+    let emulator = RefCell::new(Emulator::new());
+    let mut display: Display<_, _, _, _, R> = Display::new(Emulator::uc8151::<R>(&emulator))
+        .await
+        .unwrap();
+    *display.framebuffer_mut() = Framebuffer::stripes();
+    display.push_full_to_display().await.unwrap();
+
+    let size = (emulator.borrow().width(), emulator.borrow().height());
+    assert_eq!(size, (R::WIDTH, R::HEIGHT));
+    assert_shows(&emulator, display.framebuffer());
+}
+
+#[test]
+fn resolution_from_tres() {
+    block_on(async {
+        check_resolution::<Res96x230>().await;
+        check_resolution::<Res96x252>().await;
+        check_resolution::<Res128x296>().await;
+        check_resolution::<Res160x296>().await;
+    });
+}
+
+#[test]
+fn resolution_from_psr() {
+    let resolutions = [
+        (PsrFlags::RES_96x230, (230, 96)),
+        (PsrFlags::RES_96x252, (252, 96)),
+        (PsrFlags::RES_128x296, (296, 128)),
+        (PsrFlags::RES_160x296, (296, 160)),
+    ];
+    for (psr, size) in resolutions {
+        let emulator = RefCell::new(Emulator::new());
+        block_on(async {
+            let mut uc8151 = Emulator::uc8151::<Res128x296>(&emulator);
+            uc8151.command(Register::PSR, &[psr.bits()]).await.unwrap();
+        });
+        let emulator = emulator.borrow();
+        assert_eq!((emulator.width(), emulator.height()), size);
+    }
+}
+
+#[test]
+fn black_and_red_planes() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let mut display = TriColorDisplay::<_, _, _, _>::new(Emulator::uc8151(&emulator))
+            .await
+            .unwrap();
+        let colors = [TriColor::White, TriColor::Black, TriColor::Red];
+        for x in 0..Framebuffer::<Res128x296>::WIDTH {
+            for y in 0..Framebuffer::<Res128x296>::HEIGHT {
+                let color = colors[(x + 2 * y) % 3];
+                display.framebuffer_mut().write(x, y, color).unwrap();
+            }
+        }
+        display.push_to_display().await.unwrap();
+
+        assert_panel(&emulator, |x, y| {
+            match display.framebuffer().get(x, y).unwrap() {
+                TriColor::White => PanelColor::White,
+                TriColor::Black => PanelColor::Black,
+                TriColor::Red => PanelColor::Red,
+            }
+        });
+    });
+}
+
+#[test]
+fn updates_need_matching_format() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let framebuffer = Framebuffer::<Res128x296>::default();
+        let bytes = framebuffer.as_bytes();
+        let window = Rectangle::new(Point::new(0, 8), Size::new(16, 8));
+
+        let mut uc8151 = Emulator::uc8151::<Res128x296>(&emulator);
+        uc8151.set_format(ColorFormat::BlackWhiteRed);
+        uc8151.init(Uc8151Config::default()).await.unwrap();
+        assert_eq!(uc8151.update(bytes).await, Err(Error::WrongFormat));
+        assert_eq!(
+            uc8151.partial_update(bytes, window).await,
+            Err(Error::WrongFormat)
+        );
+        assert_eq!(
+            uc8151.update_gray(bytes, bytes).await,
+            Err(Error::WrongFormat)
+        );
+
+        uc8151.set_format(ColorFormat::BlackWhite);
+        uc8151.init(Uc8151Config::default()).await.unwrap();
+        assert_eq!(
+            uc8151.update_bwr(bytes, bytes).await,
+            Err(Error::WrongFormat)
+        );
+        assert_eq!(emulator.borrow().refreshes(), 0);
+
+        // the display selects black and white itself
+        uc8151.set_format(ColorFormat::BlackWhiteRed);
+        let mut display: Display<_, _, _, _> = Display::new(uc8151).await.unwrap();
+        display.push_to_display().await.unwrap();
+        assert_eq!(emulator.borrow().refreshes(), 1);
+        assert_shows(&emulator, display.framebuffer());
+    });
+}
+
+#[test]
+fn clean_refreshes_need_black_and_white() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let framebuffer = Framebuffer::<Res128x296>::default();
+        let bytes = framebuffer.as_bytes();
+
+        let mut uc8151 = Emulator::uc8151::<Res128x296>(&emulator);
+        uc8151.set_format(ColorFormat::BlackWhiteRed);
+        uc8151.init(Uc8151Config::default()).await.unwrap();
+        uc8151.set_speed(RefreshSpeed::Fast).await.unwrap();
+        assert_eq!(uc8151.flash().await, Err(Error::WrongFormat));
+        assert_eq!(uc8151.clean_update(bytes).await, Err(Error::WrongFormat));
+        assert_eq!(uc8151.deep_clean(bytes, 1).await, Err(Error::WrongFormat));
+        assert_eq!(emulator.borrow().refreshes(), 0);
+
+        // the selected speed survives the normal waveform of the clean passes
+        uc8151.set_format(ColorFormat::BlackWhite);
+        uc8151.init(Uc8151Config::default()).await.unwrap();
+        uc8151.flash().await.unwrap();
+        uc8151.deep_clean(bytes, 1).await.unwrap();
+        assert_eq!(emulator.borrow().refreshes(), 4);
+        assert_eq!(uc8151.speed(), RefreshSpeed::Fast);
+        assert_panel(&emulator, |_, _| PanelColor::White);
+    });
+}
+
+#[test]
+fn register_reads() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let mut uc8151 = Emulator::uc8151::<Res128x296>(&emulator);
+        uc8151.init(Uc8151Config::default()).await.unwrap();
+
+        assert_eq!(
+            uc8151.revision().await,
+            Ok(Revision {
+                lut: [0x00, 0x00, 0x00],
+                chip: 0x0c
+            })
+        );
+        assert_eq!(uc8151.status().await, Ok(FlgFlags::BUSY_N | FlgFlags::POF));
+        emulator.borrow_mut().set_temperature(-42);
+        assert_eq!(uc8151.temperature().await, Ok(Temperature(-42)));
+        assert_eq!(Temperature(-42).celsius(), -6);
+        assert_eq!(uc8151.low_power().await, Ok(false));
+        assert_eq!(uc8151.measure_vcom().await, Ok(0b011110));
+
+        // a data line wired to MOSI only can't be read
+        uc8151.set_readable(false);
+        assert_eq!(uc8151.revision().await, Err(Error::ReadUnsupported));
+        assert_eq!(uc8151.status().await, Err(Error::ReadUnsupported));
+        assert_eq!(uc8151.temperature().await, Err(Error::ReadUnsupported));
+        assert_eq!(uc8151.low_power().await, Err(Error::ReadUnsupported));
+        assert_eq!(uc8151.measure_vcom().await, Err(Error::ReadUnsupported));
+    });
+}
+
+#[test]
+fn deep_sleep_and_wake() {
+    let emulator = RefCell::new(Emulator::new());
+    block_on(async {
+        let mut display: Display<_, _, _, _> =
+            Display::new(Emulator::uc8151(&emulator)).await.unwrap();
+        *display.framebuffer_mut() = Framebuffer::stripes();
+        display.push_full_to_display().await.unwrap();
+
+        display.sleep().await.unwrap();
+        assert!(emulator.borrow().is_sleeping());
+        // the panel keeps its contents while asleep
+        assert_shows(&emulator, display.framebuffer());
+
+        display.wake().await.unwrap();
+        assert!(!emulator.borrow().is_sleeping());
+
+        // pushing wakes the controller by itself
+        display.sleep().await.unwrap();
+        display.framebuffer_mut().invert();
+        display.push_to_display().await.unwrap();
+        assert!(!emulator.borrow().is_sleeping());
+        assert_shows(&emulator, display.framebuffer());
+    });
+}
@@ -0,0 +1,22 @@
+//! Renders the screens of the firmware into framebuffers and compares them
+//! with the PBM snapshots in `tests/snapshots`.
+//!
+//! Run with `BLESS_SNAPSHOTS=1` after an intended change to update them.
+
+use embassy_badger2040::{assert_snapshot, draw_badge, Framebuffer, Res128x296};
+
+fn snapshot(name: &str) -> String {
+    format!(
+        "{}/tests/snapshots/{}.pbm",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
+/// The name badge drawn by `main.rs`.
+#[test]
+fn badge_text_box() {
+    let mut framebuffer = Framebuffer::<Res128x296>::default();
+    draw_badge(&mut framebuffer).unwrap();
+    assert_snapshot(&framebuffer, snapshot("badge_text_box"));
+}